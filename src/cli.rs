@@ -0,0 +1,203 @@
+use std::{io::Write, path::Path};
+
+use crate::{
+    formats::csv::{export_csv, history_csv, splits_csv, CsvTable},
+    run::{category::Category, game::Game, history::History, integrity::check},
+    set_data_path,
+    settings::{config::Config, profile::set_profile},
+    Error,
+};
+
+const USAGE: &str = "Usage: hitsplit [--data-dir <DIR>] [--profile <NAME>] [COMMAND]
+
+Without a command, the HitSplit window is opened.
+
+Options:
+  --data-dir <DIR>
+      Keeps the data in DIR instead of the default location. The HITSPLIT_DATA_DIR
+      environment variable does the same.
+  --profile <NAME>
+      Uses the data of profile NAME, which must exist. The HITSPLIT_PROFILE
+      environment variable does the same. Without it, the profile last chosen in the
+      manager is used.
+
+Commands:
+  check-data [--fix]
+      Lists orphan files, references to missing games and categories, duplicate
+      uuids and missing images. --fix repairs the problems that can be fixed.
+      Exits with 1 while problems remain.
+  export-csv <GAME> <CATEGORY> [--history] [--output <FILE>]
+      Writes the split table of a category as CSV, or its attempt history with
+      --history. GAME and CATEGORY are names or uuids. Prints to the standard
+      output when no file is given.
+  help
+      Shows this message.";
+
+/// Release builds on Windows are GUI programs without a console, so the output of commands
+/// would be lost. Attaching to the console HitSplit was started from brings it back.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails when there is no parent console, or when HitSplit already has one.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// Runs the command given on the command line. Returns the exit code, or `None` when
+/// there is no command and the window should be opened.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let mut args = args;
+    let mut profile = None;
+    loop {
+        match args {
+            [option, dir, rest @ ..] if option == "--data-dir" => {
+                set_data_path(dir);
+                args = rest;
+            }
+            [option, name, rest @ ..] if option == "--profile" => {
+                profile = Some(name);
+                args = rest;
+            }
+            [option, ..] if option == "--data-dir" || option == "--profile" => {
+                attach_console();
+                eprintln!("{option} needs a value\n\n{USAGE}");
+                return Some(2);
+            }
+            _ => break,
+        }
+    }
+    // The profile is looked for in the data directory, so it is set once both are known.
+    if let Some(Err(e)) = profile.map(|p| set_profile(p)) {
+        attach_console();
+        eprintln!("{e}");
+        return Some(2);
+    }
+    let (command, args) = args.split_first()?;
+    attach_console();
+    let result = match command.as_str() {
+        "check-data" => check_data_command(args),
+        "export-csv" => export_csv_command(args),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => {
+            eprintln!("Unknown command \"{command}\"\n\n{USAGE}");
+            return Some(2);
+        }
+    };
+    match result {
+        Ok(_) => Some(0),
+        Err(e) => {
+            eprintln!("{e}");
+            Some(1)
+        }
+    }
+}
+
+fn check_data_command(args: &[String]) -> Result<(), Error> {
+    let fix = match args {
+        [] => false,
+        [arg] if arg == "--fix" => true,
+        _ => {
+            return Err(Error::invalid(format!(
+                "Unknown arguments for check-data\n\n{USAGE}"
+            )))
+        }
+    };
+    let problems = check()?;
+    let mut remaining = 0;
+    for problem in problems.iter() {
+        if fix && problem.fixable() {
+            problem.repair()?;
+            println!("Fixed: {}", problem.description());
+        } else {
+            println!("{}", problem.description());
+            remaining += 1;
+        }
+    }
+    match remaining {
+        0 if problems.is_empty() => {
+            println!("No problems found");
+            Ok(())
+        }
+        0 => Ok(()),
+        _ => Err(Error::invalid(format!("Problems found: {remaining}"))),
+    }
+}
+
+fn export_csv_command(args: &[String]) -> Result<(), Error> {
+    let mut names = Vec::new();
+    let mut table = CsvTable::Splits;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--history" => table = CsvTable::History,
+            "--output" | "-o" => output = args.next(),
+            _ => names.push(arg),
+        }
+    }
+    let (game, category) = match names.as_slice() {
+        [game, category] => (game.as_str(), category.as_str()),
+        _ => {
+            return Err(Error::invalid(format!(
+                "export-csv needs a game and a category\n\n{USAGE}"
+            )))
+        }
+    };
+
+    let config = Config::load()?;
+    let category = find_category(&config, game, category)?;
+    match output {
+        Some(path) => export_csv(Path::new(path), &category, table, &config.chrono_format),
+        None => {
+            let csv = match table {
+                CsvTable::Splits => splits_csv(&category, &config.chrono_format),
+                CsvTable::History => history_csv(
+                    &History::load(category.uuid.clone())?,
+                    &config.chrono_format,
+                ),
+            };
+            match std::io::stdout().write_all(csv.as_bytes()) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(Error::io(
+                    "Could not write CSV to the standard output".to_string(),
+                    e.to_string(),
+                )),
+            }
+        }
+    }
+}
+
+/// Finds a category by the name or uuid of its game and its own.
+fn find_category(config: &Config, game: &str, category: &str) -> Result<Category, Error> {
+    let small_game = match config
+        .game_list
+        .iter()
+        .find(|g| g.uuid == game || g.name == game)
+    {
+        Some(g) => g,
+        None => return Err(Error::missing(format!("Game \"{game}\" not found"))),
+    };
+    let loaded_game = Game::load(small_game.uuid.clone())?;
+    match loaded_game
+        .categories
+        .iter()
+        .find(|c| c.uuid == category || c.name == category)
+    {
+        Some(c) => Category::load(c.uuid.clone()),
+        None => Err(Error::missing(format!(
+            "Category \"{category}\" not found in game \"{}\"",
+            small_game.name
+        ))),
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::Display;
+
+use crate::logging;
+
+/// Errors of HitSplit. Every error is logged when it is created.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Reading or writing a file, the database or a socket failed.
+    Io {
+        message: String,
+        reported_error: String,
+    },
+    /// Stored, imported or exported data could not be parsed or serialized.
+    Parse {
+        message: String,
+        reported_error: String,
+    },
+    /// A global hotkey could not be registered.
+    Hotkey {
+        message: String,
+        reported_error: String,
+    },
+    /// A game, category, split, profile or other data that is needed does not exist.
+    MissingData { message: String },
+    /// An action that can not be done with the given input or in the current state.
+    Invalid { message: String },
+}
+
+impl Error {
+    pub fn io(message: String, reported_error: String) -> Self {
+        Error::Io {
+            message,
+            reported_error,
+        }
+        .logged()
+    }
+
+    pub fn parse(message: String, reported_error: String) -> Self {
+        Error::Parse {
+            message,
+            reported_error,
+        }
+        .logged()
+    }
+
+    pub fn hotkey(message: String, reported_error: String) -> Self {
+        Error::Hotkey {
+            message,
+            reported_error,
+        }
+        .logged()
+    }
+
+    pub fn missing(message: String) -> Self {
+        Error::MissingData { message }.logged()
+    }
+
+    pub fn invalid(message: String) -> Self {
+        Error::Invalid { message }.logged()
+    }
+
+    fn logged(self) -> Self {
+        match self.reported_error() {
+            Some(reported_error) => {
+                logging::error(&format!("{} - {reported_error}", self.message()))
+            }
+            None => logging::error(self.message()),
+        }
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::Io { message, .. }
+            | Error::Parse { message, .. }
+            | Error::Hotkey { message, .. }
+            | Error::MissingData { message }
+            | Error::Invalid { message } => message,
+        }
+    }
+
+    /// Error given by the library or system call that failed, if any.
+    pub fn reported_error(&self) -> Option<&str> {
+        match self {
+            Error::Io { reported_error, .. }
+            | Error::Parse { reported_error, .. }
+            | Error::Hotkey { reported_error, .. } => Some(reported_error),
+            Error::MissingData { .. } | Error::Invalid { .. } => None,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Io { .. } => "I/O error",
+            Error::Parse { .. } => "Parse error",
+            Error::Hotkey { .. } => "Hotkey error",
+            Error::MissingData { .. } => "Missing data",
+            Error::Invalid { .. } => "Invalid action",
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for Error {}
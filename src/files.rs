@@ -0,0 +1,167 @@
+use std::{
+    fs::{read_dir, File},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{get_config_path, Error};
+
+/// Backups kept for each file. The oldest one is removed when a new one is made.
+const BACKUP_COUNT: usize = 10;
+/// A new backup is only made when the newest one is at least this old, so autosaves do
+/// not rotate older versions out.
+const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const BACKUPS_DIR: &str = "backups";
+
+/// Previous version of a file of the config directory, stored as
+/// `backups/{folder}/{name}.{seconds since epoch}.json`.
+#[derive(Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// File of the config directory the backup was made from, relative to it.
+    pub file: String,
+    pub created: SystemTime,
+}
+
+impl Backup {
+    /// Describes the backed up file, using the name stored in it when there is one.
+    pub fn description(&self) -> String {
+        let kind = match self.file.split_once('/') {
+            Some(("games", _)) => "Game",
+            Some(("categories", _)) => "Category",
+            Some(("history", _)) => "History",
+            _ => return self.file.clone(),
+        };
+        let name = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .and_then(|json| json.get("name")?.as_str().map(str::to_owned));
+        match name {
+            Some(name) => format!("{kind} \"{name}\""),
+            None => format!("{kind} {}", self.file),
+        }
+    }
+
+    /// Writes the backup over its file. The current contents are backed up first, even
+    /// if the last backup is recent.
+    pub fn restore(&self) -> Result<(), Error> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) => {
+                return Err(Error::io(
+                    format!("Could not read backup \"{}\"", self.path.display()),
+                    e.to_string(),
+                ))
+            }
+        };
+        let path = PathBuf::from(format!("{}/{}", get_config_path(), self.file));
+        let result = match path.exists() {
+            true => backup(&self.file, &path, true),
+            false => Ok(()),
+        };
+        match result.and_then(|_| write(&self.file, &contents)) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::io(
+                format!("Could not restore backup of \"{}\"", self.file),
+                e.to_string(),
+            )),
+        }
+    }
+}
+
+/// Replaces `file`, relative to the config directory, with `contents`. The contents are
+/// written to a temporary file that is then renamed over the old one, so the file is
+/// never left half written. The old contents are kept as a backup.
+pub fn write(file: &str, contents: &str) -> std::io::Result<()> {
+    let config_path = get_config_path();
+    let path = PathBuf::from(format!("{config_path}/{file}"));
+    if path.exists() {
+        backup(file, &path, false)?;
+    } else if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder)?;
+    }
+
+    let tmp_path = path.with_extension("json.tmp");
+    let mut tmp = File::create(&tmp_path)?;
+    tmp.write_all(contents.as_bytes())?;
+    tmp.sync_all()?;
+    std::fs::rename(&tmp_path, &path)
+}
+
+/// Removes `file`, relative to the config directory, keeping its contents as a backup.
+pub fn remove(file: &str) -> std::io::Result<()> {
+    let path = PathBuf::from(format!("{}/{file}", get_config_path()));
+    backup(file, &path, true)?;
+    std::fs::remove_file(path)
+}
+
+/// Copies `path` into the backups folder and removes the oldest backups of it. Nothing is
+/// copied when the newest backup has the same contents or, unless `force`, is recent.
+fn backup(file: &str, path: &Path, force: bool) -> std::io::Result<()> {
+    let contents = std::fs::read(path)?;
+    if let Some(newest) = file_backups(file).first() {
+        let recent = !force
+            && newest
+                .created
+                .elapsed()
+                .is_ok_and(|age| age < BACKUP_INTERVAL);
+        if recent || std::fs::read(&newest.path).is_ok_and(|b| b == contents) {
+            return Ok(());
+        }
+    }
+
+    let config_path = get_config_path();
+    let stem = file.strip_suffix(".json").unwrap_or(file);
+    let backup_path = PathBuf::from(format!("{config_path}/{BACKUPS_DIR}/{stem}"));
+    if let Some(folder) = backup_path.parent() {
+        std::fs::create_dir_all(folder)?;
+    }
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    std::fs::write(
+        backup_path.with_extension(format!("{seconds}.json")),
+        contents,
+    )?;
+
+    for old in file_backups(file).iter().skip(BACKUP_COUNT) {
+        std::fs::remove_file(&old.path)?;
+    }
+    Ok(())
+}
+
+fn file_backups(file: &str) -> Vec<Backup> {
+    let mut backups = all_backups();
+    backups.retain(|b| b.file == file);
+    backups
+}
+
+/// Every backup of the config directory, newest first.
+pub fn all_backups() -> Vec<Backup> {
+    let backups_path = format!("{}/{BACKUPS_DIR}", get_config_path());
+    let folders = ["", "games/", "categories/", "history/"];
+    let mut backups: Vec<Backup> = folders
+        .iter()
+        .filter_map(|folder| Some((folder, read_dir(format!("{backups_path}/{folder}")).ok()?)))
+        .flat_map(|(folder, entries)| {
+            entries
+                .flatten()
+                .filter_map(move |entry| parse_backup(folder, entry.path()))
+        })
+        .collect();
+    backups.sort_by(|a, b| b.created.cmp(&a.created));
+    backups
+}
+
+fn parse_backup(folder: &str, path: PathBuf) -> Option<Backup> {
+    let name = path.file_name()?.to_str()?;
+    let (stem, seconds) = name.strip_suffix(".json")?.rsplit_once('.')?;
+    let created = UNIX_EPOCH + Duration::from_secs(seconds.parse().ok()?);
+    Some(Backup {
+        file: format!("{folder}{stem}.json"),
+        path,
+        created,
+    })
+}
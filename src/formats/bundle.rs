@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use uuid::Uuid;
+use zip::{result::ZipError, write::SimpleFileOptions, ZipArchive, ZipWriter};
+
+use crate::{
+    run::{
+        assets,
+        category::{Category, SmallCategory},
+        game::Game,
+        history::History,
+    },
+    schema::{self, Versioned},
+    storage::{self, Record},
+    Error,
+};
+
+use super::ImageData;
+
+/// Categories and options chosen before exporting a game bundle.
+pub struct BundleExport {
+    pub categories: Vec<(SmallCategory, bool)>,
+    pub history: bool,
+}
+
+impl BundleExport {
+    pub fn new(game: &Game) -> Self {
+        Self {
+            categories: game.categories.iter().map(|c| (c.clone(), true)).collect(),
+            history: true,
+        }
+    }
+}
+
+/// Game shared as a single zip: `game.json`, `categories/{uuid}.json`, optionally
+/// `history/{uuid}.json`, and every referenced image under `images/`. Icon paths inside
+/// the bundle are relative to its root.
+pub fn export_bundle(
+    path: &Path,
+    game: &Game,
+    categories: &[Category],
+    history: bool,
+) -> Result<(), Error> {
+    let error = |e: ZipError| {
+        Error::io(
+            format!("Could not write game bundle \"{}\"", path.display()),
+            e.to_string(),
+        )
+    };
+    let file = match File::create(path) {
+        Ok(f) => f,
+        Err(e) => {
+            return Err(Error::io(
+                format!("Could not create game bundle \"{}\"", path.display()),
+                e.to_string(),
+            ))
+        }
+    };
+    let mut zip = ZipWriter::new(file);
+    let mut images = BundleImages::default();
+
+    let mut game = game.clone();
+    game.categories
+        .retain(|c| categories.iter().any(|category| category.uuid == c.uuid));
+    game.icon_path = images.add(&game.icon_path);
+
+    for category in categories.iter() {
+        let mut category = category.clone();
+        category.icon_path = images.add(&category.icon_path);
+        for split in category.splits.iter_mut() {
+            split.icon_path = images.add(&split.icon_path);
+        }
+        write_json(
+            &mut zip,
+            &format!("categories/{}.json", category.uuid),
+            &category,
+        )?;
+        if history {
+            let history = History::load(category.uuid.clone())?;
+            if !history.attempts.is_empty() {
+                write_json(
+                    &mut zip,
+                    &format!("history/{}.json", category.uuid),
+                    &history,
+                )?;
+            }
+        }
+    }
+    write_json(&mut zip, "game.json", &game)?;
+
+    for (name, bytes) in images.files.iter() {
+        zip.start_file(name.as_str(), SimpleFileOptions::default())
+            .map_err(error)?;
+        if let Err(e) = zip.write_all(bytes) {
+            return Err(error(e.into()));
+        }
+    }
+    zip.finish().map_err(error)?;
+    Ok(())
+}
+
+/// Images added to a bundle, named after their position so equal paths are stored once.
+#[derive(Default)]
+struct BundleImages {
+    names: HashMap<PathBuf, String>,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl BundleImages {
+    /// Returns the path of the image inside the bundle. Missing images are dropped.
+    fn add(&mut self, path: &Option<PathBuf>) -> Option<PathBuf> {
+        let path = path.as_ref()?;
+        if let Some(name) = self.names.get(path) {
+            return Some(PathBuf::from(name));
+        }
+        let bytes = std::fs::read(assets::resolve(path)).ok()?;
+        let image = ImageData::find(&bytes)?;
+        let name = format!("images/{}.{}", self.files.len(), image.extension);
+        self.files.push((name.clone(), image.bytes.to_vec()));
+        self.names.insert(path.clone(), name.clone());
+        Some(PathBuf::from(name))
+    }
+}
+
+fn write_json<T: Versioned>(zip: &mut ZipWriter<File>, name: &str, value: &T) -> Result<(), Error> {
+    let json = match schema::to_string(value) {
+        Ok(json) => json,
+        Err(e) => {
+            return Err(Error::parse(
+                format!("Could not serialize \"{name}\" for the game bundle"),
+                e.to_string(),
+            ))
+        }
+    };
+    let result = zip
+        .start_file(name, SimpleFileOptions::default())
+        .and_then(|_| zip.write_all(json.as_bytes()).map_err(ZipError::from));
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(
+            format!("Could not write \"{name}\" to the game bundle"),
+            e.to_string(),
+        )),
+    }
+}
+
+pub struct BundleCategory {
+    pub category: Category,
+    pub history: Option<History>,
+    /// Whether the category is imported, chosen in the preview.
+    pub selected: bool,
+}
+
+/// Contents of a game bundle, ready to be previewed before importing.
+pub struct BundleImport {
+    pub game: Game,
+    pub categories: Vec<BundleCategory>,
+    images: HashMap<String, ImageData>,
+    /// Keep the current hits and times of each split.
+    pub hits: bool,
+    /// Keep the personal best hits and times.
+    pub pb: bool,
+    /// Keep the recorded attempts.
+    pub history: bool,
+    /// When a game with the same uuid exists, import as a separate copy instead of adding
+    /// the categories to it.
+    pub as_copy: bool,
+}
+
+impl BundleImport {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let error = |e: String| {
+            Error::io(
+                format!("Could not read game bundle \"{}\"", path.display()),
+                e,
+            )
+        };
+        let file = File::open(path).map_err(|e| error(e.to_string()))?;
+        let mut zip = ZipArchive::new(file).map_err(|e| error(e.to_string()))?;
+
+        let game: Game = read_json(&mut zip, "game.json").map_err(error)?;
+        let mut categories = Vec::new();
+        for small in game.categories.iter() {
+            let name = format!("categories/{}.json", small.uuid);
+            let category = read_json(&mut zip, &name).map_err(error)?;
+            let history = read_json(&mut zip, &format!("history/{}.json", small.uuid)).ok();
+            categories.push(BundleCategory {
+                category,
+                history,
+                selected: true,
+            });
+        }
+
+        let mut images = HashMap::new();
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).map_err(|e| error(e.to_string()))?;
+            if !entry.name().starts_with("images/") {
+                continue;
+            }
+            let mut bytes = Vec::new();
+            entry
+                .read_to_end(&mut bytes)
+                .map_err(|e| error(e.to_string()))?;
+            if let Some(image) = ImageData::find(&bytes) {
+                images.insert(entry.name().to_owned(), image);
+            }
+        }
+
+        Ok(Self {
+            game,
+            categories,
+            images,
+            hits: false,
+            pb: true,
+            history: true,
+            as_copy: false,
+        })
+    }
+
+    /// Writes the selected categories and adds them to `existing`, the installed game with
+    /// the same uuid, if any. Categories whose uuid is already taken get a new one.
+    /// Returns the imported game and the first imported category.
+    pub fn import(&self, existing: Option<Game>) -> Result<(Game, Option<Category>), Error> {
+        let mut game = match existing {
+            Some(game) if !self.as_copy => game,
+            Some(_) => {
+                let mut game = self.game.clone();
+                game.uuid = Uuid::new_v4().to_string();
+                game.categories.clear();
+                game.icon_path = None;
+                game
+            }
+            None => {
+                let mut game = self.game.clone();
+                game.categories.clear();
+                game.icon_path = None;
+                game
+            }
+        };
+        if game.icon_path.is_none() {
+            game.icon_path = self.save_image(&self.game.icon_path)?;
+        }
+
+        let mut first = None;
+        for bundled in self.categories.iter().filter(|c| c.selected) {
+            let mut category = bundled.category.clone();
+            let record = Record::Category(category.uuid.clone());
+            if storage::read(&record)?.is_some() {
+                category.uuid = Uuid::new_v4().to_string();
+            }
+            category.icon_path = self.save_image(&category.icon_path)?;
+            for split in category.splits.iter_mut() {
+                split.icon_path = self.save_image(&split.icon_path)?;
+                if !self.hits {
+                    split.hits = 0;
+                    split.way_hits = 0;
+                    split.real_time = Duration::default();
+                    split.paused_time = Duration::default();
+                }
+                if !self.pb {
+                    split.pb = 0;
+                    split.pb_time = None;
+                    split.gold_time = None;
+                }
+            }
+
+            if self.history {
+                if let Some(history) = &bundled.history {
+                    let mut history = history.clone();
+                    history.category_uuid.clone_from(&category.uuid);
+                    history.save()?;
+                }
+            } else {
+                category.attempts = 0;
+            }
+            category.save()?;
+
+            game.categories.retain(|c| c.uuid != category.uuid);
+            game.categories.push(SmallCategory {
+                uuid: category.uuid.clone(),
+                name: category.name.clone(),
+            });
+            first.get_or_insert(category);
+        }
+        game.save()?;
+        Ok((game, first))
+    }
+
+    fn save_image(&self, path: &Option<PathBuf>) -> Result<Option<PathBuf>, Error> {
+        let image = path
+            .as_ref()
+            .and_then(|p| p.to_str())
+            .and_then(|p| self.images.get(p));
+        match image {
+            Some(image) => Ok(Some(image.save()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn read_json<T: Versioned>(zip: &mut ZipArchive<File>, name: &str) -> Result<T, String> {
+    let mut json = String::new();
+    zip.by_name(name)
+        .map_err(|e| format!("{name}: {e}"))?
+        .read_to_string(&mut json)
+        .map_err(|e| format!("{name}: {e}"))?;
+    schema::from_str(&json).map_err(|e| format!("{name}: {e}"))
+}
@@ -0,0 +1,114 @@
+use std::{path::Path, time::Duration};
+
+use crate::{
+    run::{
+        category::Category,
+        chrono::{duration_chrono_format, ChronometerFormat},
+        history::History,
+    },
+    Error,
+};
+
+use super::rfc3339;
+
+/// Tables of a category that can be exported as CSV.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum CsvTable {
+    /// One row per split, as shown in the split table.
+    Splits,
+    /// One row per split of every recorded attempt.
+    History,
+}
+
+pub fn export_csv(
+    path: &Path,
+    category: &Category,
+    table: CsvTable,
+    format: &ChronometerFormat,
+) -> Result<(), Error> {
+    let csv = match table {
+        CsvTable::Splits => splits_csv(category, format),
+        CsvTable::History => history_csv(&History::load(category.uuid.clone())?, format),
+    };
+    match std::fs::write(path, csv) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(
+            format!("Could not export CSV file \"{}\"", path.display()),
+            e.to_string(),
+        )),
+    }
+}
+
+pub fn splits_csv(category: &Category, format: &ChronometerFormat) -> String {
+    let mut csv =
+        String::from("split,name,hits,way_hits,pb,diff,time_ms,time,cumulative_ms,cumulative\n");
+    let mut cumulative = Duration::default();
+    for (i, split) in category.splits.iter().enumerate() {
+        cumulative += split.real_time;
+        push_row(
+            &mut csv,
+            &[
+                (i + 1).to_string(),
+                split.name.clone(),
+                split.hits.to_string(),
+                split.way_hits.to_string(),
+                split.pb.to_string(),
+                (i32::from(split.total_hits()) - i32::from(split.pb)).to_string(),
+                split.real_time.as_millis().to_string(),
+                duration_chrono_format(split.real_time, format),
+                cumulative.as_millis().to_string(),
+                duration_chrono_format(cumulative, format),
+            ],
+        );
+    }
+    csv
+}
+
+pub fn history_csv(history: &History, format: &ChronometerFormat) -> String {
+    let mut csv = String::from(
+        "attempt,started_at,ended_at,completed,split,name,hits,time_ms,time,cumulative_ms,cumulative,paused_ms,paused\n",
+    );
+    for (attempt_number, attempt) in history.attempts.iter().enumerate() {
+        let started_at = attempt.started_at.map(rfc3339).unwrap_or_default();
+        let ended_at = rfc3339(attempt.ended_at);
+        let mut cumulative = Duration::default();
+        for (i, split) in attempt.splits.iter().enumerate() {
+            cumulative += split.real_time;
+            push_row(
+                &mut csv,
+                &[
+                    (attempt_number + 1).to_string(),
+                    started_at.clone(),
+                    ended_at.clone(),
+                    attempt.completed.to_string(),
+                    (i + 1).to_string(),
+                    split.name.clone(),
+                    split.hits.to_string(),
+                    split.real_time.as_millis().to_string(),
+                    duration_chrono_format(split.real_time, format),
+                    cumulative.as_millis().to_string(),
+                    duration_chrono_format(cumulative, format),
+                    split.paused_time.as_millis().to_string(),
+                    duration_chrono_format(split.paused_time, format),
+                ],
+            );
+        }
+    }
+    csv
+}
+
+/// Appends a row, quoting the fields that contain separators, quotes or line breaks.
+fn push_row(csv: &mut String, fields: &[String]) {
+    let row: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    csv.push_str(&row.join(","));
+    csv.push('\n');
+}
@@ -0,0 +1,239 @@
+use std::{path::Path, str::FromStr, time::Duration};
+
+use uuid::Uuid;
+
+use crate::{
+    run::{
+        category::{Category, SmallCategory},
+        game::Game,
+        history::History,
+        split::Split,
+    },
+    Error,
+};
+
+use super::XmlNode;
+
+pub struct HcmRow {
+    pub title: String,
+    pub hits: u16,
+    pub way_hits: u16,
+    pub pb: u16,
+    pub duration: Duration,
+    pub duration_pb: Duration,
+    pub duration_gold: Duration,
+}
+
+pub struct HcmProfile {
+    pub name: String,
+    pub attempts: u32,
+    pub session_progress: usize,
+    pub rows: Vec<HcmRow>,
+    /// Whether the profile is imported, chosen in the preview.
+    pub selected: bool,
+}
+
+/// Profiles of a HitCounterManager save file, ready to be previewed before creating
+/// categories.
+pub struct HcmImport {
+    pub game_name: String,
+    pub profiles: Vec<HcmProfile>,
+}
+
+impl HcmImport {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(xml) => Self::parse(&xml),
+            Err(e) => Err(Error::io(
+                format!(
+                    "Could not read HitCounterManager file \"{}\"",
+                    path.display()
+                ),
+                e.to_string(),
+            )),
+        }
+    }
+
+    pub fn parse(xml: &str) -> Result<Self, Error> {
+        let settings = parse_settings(xml)?;
+        let profiles = settings
+            .child("Profiles")
+            .and_then(|p| p.child("ProfileList"))
+            .map(|list| {
+                list.children("Profile")
+                    .map(|profile| HcmProfile {
+                        name: profile.child_text("Name").unwrap_or_default().to_owned(),
+                        attempts: number(profile, "Attempts"),
+                        session_progress: number(profile, "SessionProgress"),
+                        rows: profile
+                            .child("Rows")
+                            .map(|rows| {
+                                rows.children("ProfileRow")
+                                    .map(|row| HcmRow {
+                                        title: row
+                                            .child_text("Title")
+                                            .unwrap_or_default()
+                                            .to_owned(),
+                                        hits: number(row, "Hits"),
+                                        way_hits: number(row, "WayHits"),
+                                        pb: number(row, "PB"),
+                                        duration: Duration::from_millis(number(row, "Duration")),
+                                        duration_pb: Duration::from_millis(number(
+                                            row,
+                                            "DurationPB",
+                                        )),
+                                        duration_gold: Duration::from_millis(number(
+                                            row,
+                                            "DurationGold",
+                                        )),
+                                    })
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                        selected: true,
+                    })
+                    .collect::<Vec<HcmProfile>>()
+            })
+            .unwrap_or_default();
+
+        if profiles.is_empty() {
+            return Err(Error::invalid(
+                "The HitCounterManager file has no profiles".to_string(),
+            ));
+        }
+        Ok(Self {
+            game_name: "HitCounterManager".to_string(),
+            profiles,
+        })
+    }
+}
+
+impl HcmProfile {
+    /// Creates the category and adds it to `game`.
+    pub fn to_category(&self, game: &mut Game) -> Category {
+        let uuid = Uuid::new_v4().to_string();
+        let mut category = Category::new(uuid.clone(), self.name.clone());
+        category.attempts = self.attempts;
+        category.session_progress = self.session_progress;
+
+        for row in self.rows.iter() {
+            let mut split = Split::new(Some(Uuid::new_v4().to_string()));
+            row.title.clone_into(&mut split.name);
+            split.hits = row.hits;
+            split.way_hits = row.way_hits;
+            split.pb = row.pb;
+            split.real_time = row.duration;
+            split.pb_time = Some(row.duration_pb).filter(|d| !d.is_zero());
+            split.gold_time = Some(row.duration_gold).filter(|d| !d.is_zero());
+            category.splits.push(split);
+        }
+
+        game.categories.push(SmallCategory {
+            uuid,
+            name: category.name.clone(),
+        });
+        category
+    }
+}
+
+/// Writes `categories` as profiles of a HitCounterManager save file. When `path` is
+/// already a save file, profiles with the same name are replaced and everything else is
+/// kept.
+pub fn export_hcm(path: &Path, categories: &[Category]) -> Result<(), Error> {
+    let mut settings = match std::fs::read_to_string(path) {
+        Ok(xml) => parse_settings(&xml)?,
+        Err(_) => XmlNode::new("Settings")
+            .with_attribute("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance")
+            .with_attribute("xmlns:xsd", "http://www.w3.org/2001/XMLSchema"),
+    };
+
+    let mut profiles = Vec::new();
+    for category in categories.iter() {
+        let history = History::load(category.uuid.clone())?;
+        profiles.push(profile_node(category, &history));
+    }
+
+    if settings.child_text("ProfileSelected").is_none() {
+        if let Some(category) = categories.first() {
+            settings.child_or_insert("ProfileSelected").text = category.name.clone();
+        }
+    }
+    let list = settings
+        .child_or_insert("Profiles")
+        .child_or_insert("ProfileList");
+    for profile in profiles {
+        let name = profile.child_text("Name").map(str::to_owned);
+        match list
+            .children
+            .iter()
+            .position(|p| p.name == "Profile" && p.child_text("Name").map(str::to_owned) == name)
+        {
+            Some(i) => list.children[i] = profile,
+            None => list.children.push(profile),
+        }
+    }
+
+    match std::fs::write(path, settings.to_document()) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(
+            format!(
+                "Could not export HitCounterManager file \"{}\"",
+                path.display()
+            ),
+            e.to_string(),
+        )),
+    }
+}
+
+fn profile_node(category: &Category, history: &History) -> XmlNode {
+    let mut rows = XmlNode::new("Rows");
+    for split in category.splits.iter() {
+        let diff = i32::from(split.total_hits()) - i32::from(split.pb);
+        rows = rows.with_child(
+            XmlNode::new("ProfileRow")
+                .with_child(XmlNode::new("Title").with_text(split.name.clone()))
+                .with_child(XmlNode::new("Hits").with_text(split.hits.to_string()))
+                .with_child(XmlNode::new("WayHits").with_text(split.way_hits.to_string()))
+                .with_child(XmlNode::new("Diff").with_text(diff.to_string()))
+                .with_child(XmlNode::new("PB").with_text(split.pb.to_string()))
+                .with_child(millis("Duration", Some(split.real_time)))
+                .with_child(millis("DurationPB", split.pb_time))
+                .with_child(millis("DurationGold", split.gold_time)),
+        );
+    }
+
+    let attempts = category.attempts as usize + history.attempts.len();
+    XmlNode::new("Profile")
+        .with_child(XmlNode::new("Name").with_text(category.name.clone()))
+        .with_child(XmlNode::new("Attempts").with_text(attempts.to_string()))
+        .with_child(
+            XmlNode::new("SessionProgress").with_text(category.session_progress.to_string()),
+        )
+        .with_child(rows)
+}
+
+fn millis(name: &str, duration: Option<Duration>) -> XmlNode {
+    let millis = duration.map(|d| d.as_millis()).unwrap_or_default();
+    XmlNode::new(name).with_text(millis.to_string())
+}
+
+fn parse_settings(xml: &str) -> Result<XmlNode, Error> {
+    match XmlNode::parse(xml) {
+        Ok(root) if root.name == "Settings" => Ok(root),
+        Ok(root) => Err(Error::parse(
+            "The file is not a HitCounterManager save file".to_string(),
+            format!("Unexpected root element <{}>", root.name),
+        )),
+        Err(e) => Err(Error::parse(
+            "Could not parse HitCounterManager save file".to_string(),
+            e,
+        )),
+    }
+}
+
+/// Reads a numeric child element, defaulting to zero when it is missing or out of range.
+fn number<T: FromStr + Default>(node: &XmlNode, name: &str) -> T {
+    node.child_text(name)
+        .and_then(|text| text.trim().parse().ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,378 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use uuid::Uuid;
+
+use crate::{
+    run::{
+        assets,
+        category::{Category, SmallCategory},
+        chrono::SignedDuration,
+        game::Game,
+        history::History,
+        split::Split,
+    },
+    Error,
+};
+
+use super::{ImageData, XmlNode};
+
+pub struct LssSegment {
+    pub name: String,
+    pub icon: Option<ImageData>,
+    pub pb_time: Option<Duration>,
+    pub gold_time: Option<Duration>,
+}
+
+/// Contents of a LiveSplit `.lss` file, ready to be previewed before creating a category.
+pub struct LssImport {
+    /// Unique per import, so preview images are not mixed with older imports.
+    pub id: String,
+    pub game_name: String,
+    pub category_name: String,
+    pub game_icon: Option<ImageData>,
+    pub offset: SignedDuration,
+    pub segments: Vec<LssSegment>,
+}
+
+impl LssImport {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(xml) => Self::parse(&xml),
+            Err(e) => Err(Error::io(
+                format!("Could not read LiveSplit file \"{}\"", path.display()),
+                e.to_string(),
+            )),
+        }
+    }
+
+    pub fn parse(xml: &str) -> Result<Self, Error> {
+        let run = match XmlNode::parse(xml) {
+            Ok(root) if root.name == "Run" => root,
+            Ok(root) => {
+                return Err(Error::parse(
+                    "The file is not a LiveSplit splits file".to_string(),
+                    format!("Unexpected root element <{}>", root.name),
+                ))
+            }
+            Err(e) => {
+                return Err(Error::parse(
+                    "Could not parse LiveSplit splits file".to_string(),
+                    e,
+                ))
+            }
+        };
+
+        let mut segments = Vec::new();
+        let mut previous_pb = Duration::default();
+        if let Some(nodes) = run.child("Segments") {
+            for segment in nodes.children("Segment") {
+                let pb = segment
+                    .child("SplitTimes")
+                    .and_then(|times| {
+                        times
+                            .children("SplitTime")
+                            .find(|t| t.attribute("name") == Some("Personal Best"))
+                    })
+                    .and_then(|t| t.child_text("RealTime"))
+                    .and_then(parse_time)
+                    .map(SignedDuration::to_duration);
+                // LiveSplit stores cumulative times, HitSplit stores the time of each split.
+                let pb_time = pb.map(|pb| {
+                    let time = pb.saturating_sub(previous_pb);
+                    previous_pb = pb;
+                    time
+                });
+                segments.push(LssSegment {
+                    name: segment.child_text("Name").unwrap_or_default().to_owned(),
+                    icon: segment.child_text("Icon").and_then(decode_image),
+                    pb_time,
+                    gold_time: segment
+                        .child("BestSegmentTime")
+                        .and_then(|t| t.child_text("RealTime"))
+                        .and_then(parse_time)
+                        .map(SignedDuration::to_duration),
+                });
+            }
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            game_name: run.child_text("GameName").unwrap_or_default().to_owned(),
+            category_name: run
+                .child_text("CategoryName")
+                .unwrap_or_default()
+                .to_owned(),
+            game_icon: run.child_text("GameIcon").and_then(decode_image),
+            offset: run
+                .child_text("Offset")
+                .and_then(parse_time)
+                .unwrap_or_default(),
+            segments,
+        })
+    }
+
+    /// Creates the category and adds it to `game`, writing the embedded images to disk.
+    pub fn to_category(&self, game: &mut Game) -> Result<Category, Error> {
+        let uuid = Uuid::new_v4().to_string();
+        let mut category = Category::new(uuid.clone(), self.category_name.clone());
+        category.start_offset = self.offset;
+
+        for segment in self.segments.iter() {
+            let split_uuid = Uuid::new_v4().to_string();
+            let mut split = Split::new(Some(split_uuid.clone()));
+            segment.name.clone_into(&mut split.name);
+            split.pb_time = segment.pb_time;
+            split.gold_time = segment.gold_time;
+            if let Some(icon) = &segment.icon {
+                split.icon_path = Some(icon.save()?);
+            }
+            category.splits.push(split);
+        }
+
+        if game.icon_path.is_none() {
+            if let Some(icon) = &self.game_icon {
+                game.icon_path = Some(icon.save()?);
+            }
+        }
+        game.categories.push(SmallCategory {
+            uuid,
+            name: category.name.clone(),
+        });
+        Ok(category)
+    }
+}
+
+/// Header of a `System.Drawing.Bitmap` serialized with .NET's BinaryFormatter, which is
+/// how LiveSplit stores images. The image length, a byte type marker, the image bytes and
+/// a message end record follow it.
+const BITMAP_HEADER: &[u8] =
+    b"\x00\x01\x00\x00\x00\xFF\xFF\xFF\xFF\x01\x00\x00\x00\x00\x00\x00\x00\
+\x0C\x02\x00\x00\x00\x51System.Drawing, Version=4.0.0.0, Culture=neutral, \
+PublicKeyToken=b03f5f7f11d50a3a\x05\x01\x00\x00\x00\x15System.Drawing.Bitmap\x01\x00\x00\
+\x00\x04Data\x07\x02\x02\x00\x00\x00\x09\x03\x00\x00\x00\x0F\x03\x00\x00\x00";
+
+/// Writes `category` of `game` as a LiveSplit `.lss` file. Hits are kept in custom variables.
+pub fn export_lss(path: &Path, game: &Game, category: &Category) -> Result<(), Error> {
+    let history = History::load(category.uuid.clone())?;
+    match std::fs::write(path, lss_document(game, category, &history)) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(
+            format!("Could not export LiveSplit file \"{}\"", path.display()),
+            e.to_string(),
+        )),
+    }
+}
+
+fn lss_document(game: &Game, category: &Category, history: &History) -> String {
+    let mut segments = XmlNode::new("Segments");
+    for (split, pb_total) in category.splits.iter().zip(category.pb_split_times()) {
+        let mut pb = XmlNode::new("SplitTime").with_attribute("name", "Personal Best");
+        if let Some(total) = pb_total {
+            pb = pb.with_child(XmlNode::new("RealTime").with_text(format_time(total)));
+        }
+        let mut best_segment = XmlNode::new("BestSegmentTime");
+        if let Some(best) = history.best_time(split) {
+            best_segment =
+                best_segment.with_child(XmlNode::new("RealTime").with_text(format_time(best)));
+        }
+
+        segments = segments.with_child(
+            XmlNode::new("Segment")
+                .with_child(XmlNode::new("Name").with_text(split.name.clone()))
+                .with_child(XmlNode::new("Icon").with_text(encode_image(&split.icon_path)))
+                .with_child(XmlNode::new("SplitTimes").with_child(pb))
+                .with_child(best_segment)
+                .with_child(XmlNode::new("SegmentHistory")),
+        );
+    }
+
+    let hits = category.splits.iter().map(Split::total_hits).sum::<u16>();
+    let pb_hits = category.splits.iter().map(|split| split.pb).sum::<u16>();
+    let offset = match category.start_offset.is_negative() {
+        true => format!("-{}", format_time(category.start_offset.duration)),
+        false => format_time(category.start_offset.duration),
+    };
+
+    // Attempts from before the history was kept are only counted in the category.
+    let attempts = category.attempts as usize + history.attempts.len();
+
+    XmlNode::new("Run")
+        .with_attribute("version", "1.8.0")
+        .with_child(XmlNode::new("GameIcon").with_text(encode_image(&game.icon_path)))
+        .with_child(XmlNode::new("GameName").with_text(game.name.clone()))
+        .with_child(XmlNode::new("CategoryName").with_text(category.name.clone()))
+        .with_child(XmlNode::new("LayoutPath"))
+        .with_child(
+            XmlNode::new("Metadata")
+                .with_child(XmlNode::new("Run").with_attribute("id", ""))
+                .with_child(XmlNode::new("Platform").with_attribute("usesEmulator", "False"))
+                .with_child(XmlNode::new("Region"))
+                .with_child(XmlNode::new("Variables"))
+                .with_child(
+                    XmlNode::new("CustomVariables")
+                        .with_child(custom_variable("Hits", hits.to_string()))
+                        .with_child(custom_variable("PB Hits", pb_hits.to_string())),
+                ),
+        )
+        .with_child(XmlNode::new("Offset").with_text(offset))
+        .with_child(XmlNode::new("AttemptCount").with_text(attempts.to_string()))
+        .with_child(XmlNode::new("AttemptHistory"))
+        .with_child(segments)
+        .with_child(XmlNode::new("AutoSplitterSettings"))
+        .to_document()
+}
+
+fn custom_variable(name: &str, value: String) -> XmlNode {
+    XmlNode::new("Variable")
+        .with_attribute("name", name)
+        .with_text(value)
+}
+
+/// Formats a time span the way LiveSplit writes it, like `01:23:45.6700000`.
+fn format_time(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let time = format!(
+        "{:02}:{:02}:{:02}.{:07}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        duration.subsec_nanos() / 100
+    );
+    match secs / 86400 {
+        0 => time,
+        days => format!("{days}.{time}"),
+    }
+}
+
+/// Parses LiveSplit time spans, like `01:23:45.6700000`, `-00:00:01.5` or `1.02:00:00`.
+pub fn parse_time(text: &str) -> Option<SignedDuration> {
+    let text = text.trim();
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(t) => (true, t),
+        None => (false, text),
+    };
+
+    let mut parts = text.split(':');
+    let first = parts.next()?;
+    let (days, hours) = match first.split_once('.') {
+        Some((days, hours)) => (days.parse::<u64>().ok()?, hours.parse::<u64>().ok()?),
+        None => (0, first.parse::<u64>().ok()?),
+    };
+    let minutes = parts.next()?.parse::<u64>().ok()?;
+    let seconds = parts.next().unwrap_or("0").parse::<f64>().ok()?;
+    if parts.next().is_some() || !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+
+    let whole = days
+        .checked_mul(24)?
+        .checked_add(hours)?
+        .checked_mul(3600)?
+        .checked_add(minutes.checked_mul(60)?)?;
+    let duration =
+        Duration::from_secs(whole).checked_add(Duration::try_from_secs_f64(seconds).ok()?)?;
+    Some(SignedDuration { negative, duration })
+}
+
+/// Wraps the PNG or JPEG image at `path` the way LiveSplit does and encodes it as base64.
+/// Missing or unsupported images are left empty.
+fn encode_image(path: &Option<PathBuf>) -> String {
+    let image = match path
+        .as_ref()
+        .and_then(|p| std::fs::read(assets::resolve(p)).ok())
+        .and_then(|bytes| ImageData::find(&bytes))
+    {
+        Some(i) => i,
+        None => return String::new(),
+    };
+    let mut bytes = BITMAP_HEADER.to_vec();
+    bytes.extend_from_slice(&(image.bytes.len() as u32).to_le_bytes());
+    bytes.push(0x02);
+    bytes.extend_from_slice(&image.bytes);
+    bytes.push(0x0B);
+    STANDARD.encode(bytes)
+}
+
+fn decode_image(text: &str) -> Option<ImageData> {
+    let base64: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = STANDARD.decode(base64).ok()?;
+    ImageData::find(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::history::Attempt;
+
+    fn secs(text: &str) -> Option<f64> {
+        parse_time(text).map(|t| t.as_secs_f64())
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(secs("00:01:02.5000000"), Some(62.5));
+        assert_eq!(secs("1.02:00:00"), Some(93600.0));
+        assert_eq!(secs("-00:00:01.5"), Some(-1.5));
+        assert_eq!(secs(" 00:03 "), Some(180.0));
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        for text in [
+            "",
+            "12",
+            "a:00:00",
+            "00:00:-1",
+            "00:00:00:00",
+            "00:00:NaN",
+            "00:00:inf",
+        ] {
+            assert_eq!(secs(text), None, "{text} should be rejected");
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_times() {
+        assert_eq!(secs("00:00:1e300"), None);
+        assert_eq!(secs("18446744073709551615:00:00"), None);
+        assert_eq!(secs("1000000000000000.00:00:00"), None);
+        assert_eq!(secs("00:18446744073709551615:00"), None);
+        assert_eq!(secs("5124095576030431.00:00:18446744073709551615"), None);
+    }
+
+    #[test]
+    fn exports_lss() {
+        let game = Game {
+            name: "Game".to_string(),
+            ..Default::default()
+        };
+        let mut category = Category::new("uuid".to_string(), "Any%".to_string());
+        category.attempts = 2;
+        category.start_offset = SignedDuration::from_secs_f64(-1.5);
+        for (name, hits) in [("First", 1), ("Second", 3)] {
+            let mut split = Split::new(None);
+            split.name = name.to_string();
+            split.hits = hits;
+            category.splits.push(split);
+        }
+        let history = History {
+            category_uuid: category.uuid.clone(),
+            attempts: vec![Attempt::new(&category, 1, None, false)],
+        };
+
+        let xml = lss_document(&game, &category, &history);
+        let run = XmlNode::parse(&xml).unwrap();
+        assert_eq!(run.child_text("AttemptCount"), Some("3"));
+
+        let import = LssImport::parse(&xml).unwrap();
+        assert_eq!(import.game_name, "Game");
+        assert_eq!(import.category_name, "Any%");
+        assert_eq!(import.offset.as_secs_f64(), -1.5);
+        let names: Vec<&str> = import.segments.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["First", "Second"]);
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{run::assets, Error};
+
+pub mod bundle;
+pub mod csv;
+pub mod hitcountermanager;
+pub mod livesplit;
+pub mod splitsio;
+
+/// Minimal XML element tree, enough to read and write the split files of other tools.
+#[derive(Clone, Default)]
+pub struct XmlNode {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub text: String,
+    pub children: Vec<XmlNode>,
+}
+
+impl XmlNode {
+    /// Parses a document and returns its root element.
+    pub fn parse(xml: &str) -> Result<Self, String> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        let mut stack: Vec<XmlNode> = vec![XmlNode::default()];
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) => stack.push(Self::element(&e)?),
+                Ok(Event::Empty(e)) => {
+                    let node = Self::element(&e)?;
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(node);
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    let node = match stack.pop() {
+                        Some(n) if !stack.is_empty() => n,
+                        _ => return Err("Unexpected closing tag".to_string()),
+                    };
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(node);
+                    }
+                }
+                Ok(Event::Text(e)) => {
+                    let text = e.unescape().map_err(|e| e.to_string())?;
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(&text);
+                    }
+                }
+                Ok(Event::CData(e)) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text
+                            .push_str(&String::from_utf8_lossy(&e.into_inner()));
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => (),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        match stack
+            .pop()
+            .and_then(|document| document.children.into_iter().next())
+        {
+            Some(root) => Ok(root),
+            None => Err("The document has no root element".to_string()),
+        }
+    }
+
+    pub fn new(name: &str) -> Self {
+        XmlNode {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_attribute(mut self, key: &str, value: &str) -> Self {
+        self.attributes.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    pub fn with_child(mut self, child: XmlNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Serializes the element as the root of an indented UTF-8 document.
+    pub fn to_document(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        self.write(&mut xml, 0);
+        xml
+    }
+
+    fn write(&self, xml: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        xml.push_str(&format!("{indent}<{}", self.name));
+        for (key, value) in self.attributes.iter() {
+            xml.push_str(&format!(" {key}=\"{}\"", escape(value.as_str())));
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            xml.push_str(" />\n");
+        } else if self.children.is_empty() {
+            xml.push_str(&format!(
+                ">{}</{}>\n",
+                escape(self.text.as_str()),
+                self.name
+            ));
+        } else {
+            xml.push_str(">\n");
+            for child in self.children.iter() {
+                child.write(xml, depth + 1);
+            }
+            xml.push_str(&format!("{indent}</{}>\n", self.name));
+        }
+    }
+
+    fn element(start: &BytesStart<'_>) -> Result<Self, String> {
+        let mut node = XmlNode {
+            name: String::from_utf8_lossy(start.name().as_ref()).to_string(),
+            ..Default::default()
+        };
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| e.to_string())?;
+            let value = attr.unescape_value().map_err(|e| e.to_string())?;
+            node.attributes.push((
+                String::from_utf8_lossy(attr.key.as_ref()).to_string(),
+                value.to_string(),
+            ));
+        }
+        Ok(node)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// Returns the first child named `name`, adding an empty one if there is none.
+    pub fn child_or_insert(&mut self, name: &str) -> &mut XmlNode {
+        let index = match self.children.iter().position(|c| c.name == name) {
+            Some(i) => i,
+            None => {
+                self.children.push(XmlNode::new(name));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[index]
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name)
+            .map(|c| c.text.as_str())
+            .filter(|t| !t.is_empty())
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Image embedded in an imported file, not yet written to disk.
+#[derive(Clone)]
+pub struct ImageData {
+    pub bytes: Arc<[u8]>,
+    pub extension: &'static str,
+}
+
+impl ImageData {
+    /// Finds a PNG or JPEG image inside `bytes`. Some tools wrap images in their own
+    /// serialization format, so the image does not need to start at the first byte.
+    pub fn find(bytes: &[u8]) -> Option<Self> {
+        const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+        const JPEG_MAGIC: &[u8] = b"\xff\xd8\xff";
+        const JPEG_END: &[u8] = b"\xff\xd9";
+
+        if let Some(start) = find_bytes(bytes, PNG_MAGIC) {
+            let image = &bytes[start..];
+            // Walk the chunks (length, type, data and checksum) until the IEND chunk.
+            let mut end = PNG_MAGIC.len();
+            while let Some(header) = image.get(end..end + 8) {
+                let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+                end = (end + 12 + length as usize).min(image.len());
+                if &header[4..] == b"IEND" {
+                    break;
+                }
+            }
+            return Some(Self {
+                bytes: image[..end].into(),
+                extension: "png",
+            });
+        }
+        if let Some(start) = find_bytes(bytes, JPEG_MAGIC) {
+            let image = &bytes[start..];
+            let end = image
+                .windows(JPEG_END.len())
+                .rposition(|w| w == JPEG_END)
+                .map(|i| i + JPEG_END.len())
+                .unwrap_or(image.len());
+            return Some(Self {
+                bytes: image[..end].into(),
+                extension: "jpg",
+            });
+        }
+        None
+    }
+
+    /// Writes the image into the asset store and returns its reference.
+    pub fn save(&self) -> Result<PathBuf, Error> {
+        assets::store_bytes(&self.bytes, self.extension)
+    }
+}
+
+/// Formats `time` as an RFC 3339 timestamp in UTC, like `2024-05-01T18:30:00Z`.
+pub fn rfc3339(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    // Days since the epoch to a civil date, from Howard Hinnant's `civil_from_days`.
+    let z = secs / 86400 + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
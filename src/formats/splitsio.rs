@@ -0,0 +1,297 @@
+use std::{path::Path, time::Duration};
+
+use serde::Serialize;
+
+use crate::{
+    run::{category::Category, game::Game, history::History},
+    Error,
+};
+
+/// Version of the splits.io Exchange Format schema the export follows.
+const SCHEMA_VERSION: &str = "v1.0.0";
+
+#[derive(Serialize)]
+struct Timer {
+    shortname: &'static str,
+    longname: &'static str,
+    version: &'static str,
+    website: &'static str,
+}
+
+#[derive(Serialize)]
+struct Name {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shortname: Option<String>,
+    longname: String,
+}
+
+#[derive(Serialize)]
+struct RunTime {
+    #[serde(rename = "realtimeMS")]
+    realtime_ms: u128,
+}
+
+impl From<Duration> for RunTime {
+    fn from(duration: Duration) -> Self {
+        Self {
+            realtime_ms: duration.as_millis(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttemptHistory {
+    attempt_number: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<RunTime>,
+}
+
+#[derive(Serialize)]
+struct Attempts {
+    total: usize,
+    histories: Vec<AttemptHistory>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SegmentHistory {
+    attempt_number: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ended_at: Option<RunTime>,
+    is_reset: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Segment {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ended_at: Option<RunTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    best_duration: Option<RunTime>,
+    is_skipped: bool,
+    histories: Vec<SegmentHistory>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    #[serde(rename = "_schemaVersion")]
+    schema_version: &'static str,
+    timer: Timer,
+    game: Name,
+    category: Name,
+    runners: Vec<Name>,
+    attempts: Attempts,
+    segments: Vec<Segment>,
+}
+
+/// Writes `category` of `game` and its attempt history in the splits.io Exchange Format.
+pub fn export_splitsio(
+    path: &Path,
+    game: &Game,
+    category: &Category,
+    runner: &str,
+) -> Result<(), Error> {
+    let history = History::load(category.uuid.clone())?;
+    let json = match serde_json::to_string_pretty(&run(game, category, &history, runner)) {
+        Ok(json) => json,
+        Err(e) => {
+            return Err(Error::parse(
+                format!(
+                    "Could not serialize category {} with uuid {}",
+                    category.name, category.uuid
+                ),
+                e.to_string(),
+            ))
+        }
+    };
+    match std::fs::write(path, json) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(
+            format!("Could not export splits.io file \"{}\"", path.display()),
+            e.to_string(),
+        )),
+    }
+}
+
+fn run(game: &Game, category: &Category, history: &History, runner: &str) -> Run {
+    // Attempts imported from other tools come first, so numbering continues after them.
+    let first_attempt = category.attempts as usize + 1;
+
+    let segments = category
+        .splits
+        .iter()
+        .zip(category.pb_split_times())
+        .enumerate()
+        .map(|(i, (split, pb_total))| Segment {
+            name: split.name.clone(),
+            ended_at: pb_total.map(RunTime::from),
+            best_duration: history.best_time(split).map(RunTime::from),
+            is_skipped: false,
+            histories: history
+                .attempts
+                .iter()
+                .enumerate()
+                .filter(|(_, attempt)| i < attempt.splits.len())
+                .map(|(number, attempt)| {
+                    let is_reset = !attempt.completed && i + 1 == attempt.splits.len();
+                    let ended_at: Duration =
+                        attempt.splits.iter().take(i + 1).map(|s| s.real_time).sum();
+                    SegmentHistory {
+                        attempt_number: first_attempt + number,
+                        ended_at: (!is_reset).then(|| ended_at.into()),
+                        is_reset,
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    let histories = history
+        .attempts
+        .iter()
+        .enumerate()
+        .map(|(number, attempt)| AttemptHistory {
+            attempt_number: first_attempt + number,
+            duration: attempt.completed.then(|| {
+                attempt
+                    .splits
+                    .iter()
+                    .map(|s| s.real_time)
+                    .sum::<Duration>()
+                    .into()
+            }),
+        })
+        .collect();
+
+    let runners = match runner.trim() {
+        "" => Vec::new(),
+        name => vec![Name {
+            shortname: Some(name.to_lowercase().replace(char::is_whitespace, "_")),
+            longname: name.to_owned(),
+        }],
+    };
+
+    Run {
+        schema_version: SCHEMA_VERSION,
+        timer: Timer {
+            shortname: "hitsplit",
+            longname: "HitSplit",
+            version: env!("CARGO_PKG_VERSION"),
+            website: "https://github.com/vbaenal/hitsplit",
+        },
+        game: Name {
+            shortname: None,
+            longname: game.name.clone(),
+        },
+        category: Name {
+            shortname: None,
+            longname: category.name.clone(),
+        },
+        runners,
+        attempts: Attempts {
+            total: first_attempt - 1 + history.attempts.len(),
+            histories,
+        },
+        segments,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::run::{history::Attempt, split::Split};
+
+    const SCHEMA: &str = include_str!("../../tests/fixtures/splitsio/run_v1.0.0.json");
+    const SCHEMA_URL: &str = "https://splits.io/schema/run_v1.0.0.json";
+
+    fn category() -> Category {
+        let mut category = Category::new("category".to_string(), "Any%".to_string());
+        category.attempts = 2;
+        category.splits = ["First", "Second", "Last"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Split {
+                name: name.to_string(),
+                pb_time: Some(Duration::from_secs(10 + i as u64)),
+                ..Split::new(Some(format!("split-{i}")))
+            })
+            .collect();
+        category
+    }
+
+    /// A completed attempt and one reset on the second split.
+    fn history(category: &mut Category) -> History {
+        let mut attempts = Vec::new();
+        for (reached, completed) in [(3, true), (2, false)] {
+            category.splits.iter_mut().for_each(|split| {
+                split.hits = 1;
+                split.real_time = Duration::from_millis(9500);
+            });
+            attempts.push(Attempt::new(
+                category,
+                reached,
+                Some(SystemTime::now()),
+                completed,
+            ));
+        }
+        History {
+            category_uuid: category.uuid.clone(),
+            attempts,
+        }
+    }
+
+    fn validate(run: &Run) {
+        let mut schemas = boon::Schemas::new();
+        let mut compiler = boon::Compiler::new();
+        compiler.enable_format_assertions();
+        compiler
+            .add_resource(SCHEMA_URL, serde_json::from_str(SCHEMA).unwrap())
+            .unwrap();
+        let schema = compiler.compile(SCHEMA_URL, &mut schemas).unwrap();
+        let json = serde_json::to_value(run).unwrap();
+        if let Err(e) = schemas.validate(&json, schema) {
+            panic!("{e:#}\n{json:#}");
+        }
+    }
+
+    #[test]
+    fn run_follows_schema() {
+        let game = Game::new("game".to_string(), "Game".to_string());
+        let mut category = category();
+        let history = history(&mut category);
+        let run = run(&game, &category, &history, "Some Runner");
+        validate(&run);
+
+        let json = serde_json::to_value(&run).unwrap();
+        assert_eq!(json["attempts"]["total"], 4);
+        let histories = &json["attempts"]["histories"];
+        assert_eq!(histories[0]["attemptNumber"], 3);
+        assert_eq!(histories[0]["duration"]["realtimeMS"], 28500);
+        assert_eq!(histories[1].get("duration"), None);
+
+        let second = &json["segments"][1];
+        assert_eq!(second["endedAt"]["realtimeMS"], 21000);
+        assert_eq!(second["bestDuration"]["realtimeMS"], 9500);
+        assert_eq!(second["histories"][0]["endedAt"]["realtimeMS"], 19000);
+        assert_eq!(second["histories"][1]["isReset"], Value::Bool(true));
+        assert_eq!(second["histories"][1].get("endedAt"), None);
+        assert_eq!(
+            json["segments"][2]["histories"].as_array().unwrap().len(),
+            1
+        );
+        assert_eq!(json["runners"][0]["shortname"], "some_runner");
+    }
+
+    #[test]
+    fn run_without_history_follows_schema() {
+        let game = Game::new("game".to_string(), "Game".to_string());
+        validate(&run(&game, &category(), &History::default(), ""));
+    }
+}
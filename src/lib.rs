@@ -1,32 +1,24 @@
+mod cli;
+mod error;
+mod files;
+mod formats;
+mod logging;
 mod run;
+mod schema;
+mod server;
 mod settings;
+mod storage;
 mod ui;
 
-use std::{fs::OpenOptions, io::Write, path::PathBuf};
+use std::{path::PathBuf, sync::OnceLock};
 
+pub use cli::run_cli;
 use directories::ProjectDirs;
 use egui_file::FileDialog;
+pub use error::Error;
+pub use run::chrono::{Chronometer, Clock, ManualClock, MonotonicClock};
 pub use ui::hitsplit::HitSplit;
 
-#[derive(Clone)]
-pub enum Error {
-    None,
-    Error(String),
-}
-
-impl Error {
-    pub fn new(hitsplit_error: String, reported_error: String) -> Self {
-        let log = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open("hitsplit.log");
-        if let Ok(mut l) = log {
-            writeln!(l, "{hitsplit_error} - {reported_error}").unwrap_or_default();
-        }
-        Error::Error(hitsplit_error)
-    }
-}
-
 fn get_pictures_path() -> PathBuf {
     if let Some(d) = directories::UserDirs::new() {
         if let Some(p) = d.picture_dir() {
@@ -39,7 +31,49 @@ fn get_pictures_path() -> PathBuf {
     }
 }
 
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_VAR: &str = "HITSPLIT_DATA_DIR";
+/// HitSplit runs in portable mode when this file is next to the executable, keeping its
+/// data in `PORTABLE_DIR` beside it.
+const PORTABLE_MARKER: &str = "portable.txt";
+const PORTABLE_DIR: &str = "data";
+
+static DATA_PATH: OnceLock<String> = OnceLock::new();
+
+/// Uses `dir` as the data directory. Has no effect once the data directory is in use.
+pub fn set_data_path(dir: &str) {
+    let _ = DATA_PATH.set(dir.trim_end_matches(['/', '\\']).to_owned());
+}
+
+/// Directory where all profiles are kept: the one given on the command line, the one in
+/// `DATA_DIR_VAR`, the portable folder, or the config directory of the system.
+pub fn get_data_path() -> String {
+    DATA_PATH.get_or_init(default_data_path).clone()
+}
+
+/// Directory where the data of the active profile is kept.
 pub fn get_config_path() -> String {
+    settings::profile::profile_path(&settings::profile::active_profile())
+}
+
+fn portable_path() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    exe_dir
+        .join(PORTABLE_MARKER)
+        .exists()
+        .then(|| exe_dir.join(PORTABLE_DIR))
+}
+
+fn default_data_path() -> String {
+    if let Ok(dir) = std::env::var(DATA_DIR_VAR) {
+        if !dir.is_empty() {
+            return dir.trim_end_matches(['/', '\\']).to_owned();
+        }
+    }
+    if let Some(path) = portable_path().as_ref().and_then(|p| p.to_str()) {
+        return path.to_owned();
+    }
+
     let mut config_path: String = "config".to_owned();
 
     if let Some(proj_dirs) = ProjectDirs::from("", "", "HitSplit") {
@@ -0,0 +1,115 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::Write,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{formats::rfc3339, get_data_path};
+
+/// Log file in the data directory, shared by all profiles.
+pub const LOG_FILE: &str = "hitsplit.log";
+/// The log is rotated once it grows past this size.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+/// Rotated logs kept besides the current one, from `hitsplit.log.1` (the newest) to
+/// `hitsplit.log.3`.
+const ROTATED_LOGS: usize = 3;
+
+/// Severity of a log message. Messages less severe than the configured level are not
+/// written.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    pub const ALL: [Level; 4] = [Level::Error, Level::Warn, Level::Info, Level::Debug];
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        })
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+/// Keeps lines written by the server thread and the UI from mixing.
+static WRITE: Mutex<()> = Mutex::new(());
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn log_path() -> String {
+    format!("{}/{LOG_FILE}", get_data_path())
+}
+
+/// Moves `hitsplit.log` to `hitsplit.log.1`, `hitsplit.log.1` to `hitsplit.log.2` and so
+/// on, dropping the oldest.
+fn rotate(path: &str) {
+    for i in (1..ROTATED_LOGS).rev() {
+        let _ = std::fs::rename(format!("{path}.{i}"), format!("{path}.{}", i + 1));
+    }
+    let _ = std::fs::rename(path, format!("{path}.1"));
+}
+
+/// Appends a line with the time and level to the log. Failing to write is ignored, as
+/// there is nowhere to report it.
+pub fn log(level: Level, message: &str) {
+    if level as u8 > LEVEL.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(_lock) = WRITE.lock() else {
+        return;
+    };
+    let path = log_path();
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() >= MAX_LOG_SIZE) {
+        rotate(&path);
+    }
+    let _ = std::fs::create_dir_all(get_data_path());
+    if let Ok(mut file) = OpenOptions::new().append(true).create(true).open(&path) {
+        let _ = writeln!(file, "{} {level:<5} {message}", rfc3339(SystemTime::now()));
+    }
+}
+
+pub fn error(message: &str) {
+    log(Level::Error, message);
+}
+
+pub fn warn(message: &str) {
+    log(Level::Warn, message);
+}
+
+pub fn info(message: &str) {
+    log(Level::Info, message);
+}
+
+pub fn debug(message: &str) {
+    log(Level::Debug, message);
+}
+
+/// Last `count` lines of the current log, oldest first.
+pub fn tail(count: usize) -> Vec<String> {
+    let Ok(log) = std::fs::read_to_string(log_path()) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = log.lines().collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|l| l.to_string())
+        .collect()
+}
@@ -1,7 +1,14 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+use std::path::Path;
+
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = hitsplit::run_cli(&args) {
+        std::process::exit(code);
+    }
+
     let icon = include_bytes!("../icon.ico");
     let image = image::load_from_memory(icon)
         .expect("Failed to open icon path")
@@ -17,6 +24,8 @@ fn main() -> eframe::Result<()> {
                 width: icon_width,
                 height: icon_height,
             }),
+        // Window and egui state, kept with the rest of the data.
+        persistence_path: Some(Path::new(&hitsplit::get_data_path()).join("app.ron")),
         ..Default::default()
     };
     eframe::run_native(
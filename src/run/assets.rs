@@ -0,0 +1,153 @@
+use std::{
+    collections::HashSet,
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{get_config_path, storage, Error};
+
+use super::{category::Category, game::Game};
+
+/// Images are copied into this folder of the config directory, named after the hash of
+/// their contents, and referenced relative to the config directory.
+const ASSETS_DIR: &str = "assets";
+/// Folder where imported images were written before the asset store existed.
+const LEGACY_IMAGES_DIR: &str = "images";
+
+/// Copies the image at `path` into the asset store and returns its reference.
+pub fn store_file(path: &Path) -> Result<PathBuf, Error> {
+    let bytes = match std::fs::read(path) {
+        Ok(b) => b,
+        Err(e) => {
+            return Err(Error::io(
+                format!("Could not read image \"{}\"", path.display()),
+                e.to_string(),
+            ))
+        }
+    };
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("png")
+        .to_lowercase();
+    store_bytes(&bytes, &extension)
+}
+
+/// Writes an image into the asset store, unless the same image is already there, and
+/// returns its reference.
+pub fn store_bytes(bytes: &[u8], extension: &str) -> Result<PathBuf, Error> {
+    let config_path = get_config_path();
+    let assets_path = format!("{config_path}/{ASSETS_DIR}");
+    if let Err(e) = std::fs::create_dir_all(&assets_path) {
+        return Err(Error::io(
+            format!("Could not create assets directory: \"{assets_path}\""),
+            e.to_string(),
+        ));
+    }
+
+    let hash = Sha256::digest(bytes);
+    let reference = PathBuf::from(format!("{ASSETS_DIR}/{hash:x}.{extension}"));
+    let path = resolve(&reference);
+    if !path.exists() {
+        if let Err(e) = std::fs::write(&path, bytes) {
+            return Err(Error::io(
+                format!("Could not save image on path \"{}\"", path.display()),
+                e.to_string(),
+            ));
+        }
+    }
+    Ok(reference)
+}
+
+/// Absolute path of an icon. Asset references are relative to the config directory.
+pub fn resolve(path: &Path) -> PathBuf {
+    if path.is_relative() {
+        Path::new(&get_config_path()).join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// URI egui loads the icon from.
+pub fn uri(path: &Path) -> String {
+    format!("file://{}", resolve(path).display())
+}
+
+/// Copies an absolute icon into the asset store. Pictures that no longer exist are kept
+/// as they are, so they can still be found by hand.
+fn migrate_icon(icon_path: &mut Option<PathBuf>) -> bool {
+    match icon_path {
+        Some(path) if path.is_absolute() && path.exists() => match store_file(path) {
+            Ok(reference) => {
+                *icon_path = Some(reference);
+                true
+            }
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+/// Moves the absolute icons of every game and category into the asset store.
+pub fn migrate() -> Result<(), Error> {
+    for mut game in all_games()? {
+        if migrate_icon(&mut game.icon_path) {
+            game.save()?;
+        }
+    }
+    for mut category in all_categories()? {
+        let mut changed = migrate_icon(&mut category.icon_path);
+        for split in category.splits.iter_mut() {
+            changed |= migrate_icon(&mut split.icon_path);
+        }
+        if changed {
+            category.save()?;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the assets, and the images of the old images folder, that no game or category
+/// refers to. `in_use` holds references of data that may not be saved yet. Nothing is
+/// deleted if any game or category can not be read.
+pub fn cleanup(in_use: &[&Option<PathBuf>]) -> Result<(), Error> {
+    let mut referenced: HashSet<PathBuf> = in_use.iter().filter_map(|p| (*p).clone()).collect();
+    for game in all_games()? {
+        referenced.extend(game.icon_path);
+    }
+    for category in all_categories()? {
+        referenced.extend(category.icon_path);
+        referenced.extend(category.splits.into_iter().filter_map(|s| s.icon_path));
+    }
+    let referenced: HashSet<PathBuf> = referenced.iter().map(|p| resolve(p)).collect();
+
+    let config_path = get_config_path();
+    let files = [ASSETS_DIR, LEGACY_IMAGES_DIR]
+        .iter()
+        .filter_map(|folder| read_dir(format!("{config_path}/{folder}")).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()));
+    for path in files {
+        if !referenced.contains(&path) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                return Err(Error::io(
+                    format!("Could not remove unused image \"{}\"", path.display()),
+                    e.to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn all_games() -> Result<Vec<Game>, Error> {
+    storage::game_uuids()?.into_iter().map(Game::load).collect()
+}
+
+fn all_categories() -> Result<Vec<Category>, Error> {
+    storage::category_uuids()?
+        .into_iter()
+        .map(Category::load)
+        .collect()
+}
@@ -1,10 +1,17 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
 
-use crate::{get_config_path, Error};
+use crate::{
+    schema::{self, Migration, Versioned},
+    settings::shortcut::Shortcut,
+    storage::{self, Record},
+    Error,
+};
 
-use super::split::Split;
+use super::{chrono::SignedDuration, split::Split};
 
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct Category {
@@ -12,6 +19,18 @@ pub struct Category {
     pub name: String,
     pub icon_path: Option<PathBuf>,
     pub splits: Vec<Split>,
+    /// Time the chronometer starts at. Negative values delay the start of the first split.
+    #[serde(default)]
+    pub start_offset: SignedDuration,
+    /// Attempts counted by another tool before the category was imported.
+    #[serde(default)]
+    pub attempts: u32,
+    /// Furthest split reached in the current HitCounterManager session.
+    #[serde(default)]
+    pub session_progress: usize,
+    /// Bindings used instead of those of the game and the settings while it is loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcut_overrides: Option<Shortcut>,
 }
 
 impl Category {
@@ -24,30 +43,30 @@ impl Category {
     }
 
     pub fn load(uuid: String) -> Result<Self, Error> {
-        let config_path = get_config_path();
-        let category_json: String =
-            match std::fs::read_to_string(format!("{config_path}/categories/{uuid}.json")) {
-                Err(e) => return Err(Error::new(format!("Could not load category with uuid {uuid}. File not found: \"{config_path}/categories/{uuid}.json\""), e.to_string())),
-                Ok(f) => f,
-            };
+        let record = Record::Category(uuid.clone());
+        let category_json: String = match storage::read(&record)? {
+            None => {
+                return Err(Error::missing(format!(
+                    "Could not load category with uuid {uuid}. Not found: {record}"
+                )))
+            }
+            Some(f) => f,
+        };
 
-        match serde_json::from_str(category_json.as_str()) {
+        match schema::from_str(category_json.as_str()) {
             Ok(category) => Ok(category),
-            Err(e) => Err(Error::new(
-                format!(
-                    "Could not parse category json file: \"{config_path}/categories/{uuid}.json\""
-                ),
+            Err(e) => Err(Error::parse(
+                format!("Could not parse category {record}"),
                 e.to_string(),
             )),
         }
     }
 
     pub fn save(&self) -> Result<(), Error> {
-        let config_path = get_config_path();
-        let category_str = match serde_json::to_string(&self) {
+        let category_str = match schema::to_string(self) {
             Ok(category) => category,
             Err(e) => {
-                return Err(Error::new(
+                return Err(Error::parse(
                     format!(
                         "Could not serialize category {} with uuid {}",
                         self.name, self.uuid
@@ -56,27 +75,26 @@ impl Category {
                 ))
             }
         };
-        match std::fs::write(
-            format!("{config_path}/categories/{}.json", self.uuid),
-            category_str,
-        ) {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                Err(Error::new(
-                    format!(
-                        "Could not save category {0} with uuid {1} on path \"{config_path}/categories/{1}.json\"",
-                        self.name, self.uuid
-                    ),
-                    e.to_string(),
-                ))
-            }
-        }
+        storage::write(&Record::Category(self.uuid.clone()), &category_str)
     }
 
     pub fn change_name(&mut self, new_name: &str) {
         new_name.clone_into(&mut self.name)
     }
 
+    /// Cumulative time at the end of each split in the personal best run. Unknown after a
+    /// split without PB time.
+    pub fn pb_split_times(&self) -> Vec<Option<Duration>> {
+        let mut total = Some(Duration::default());
+        self.splits
+            .iter()
+            .map(|split| {
+                total = total.zip(split.pb_time).map(|(total, time)| total + time);
+                total
+            })
+            .collect()
+    }
+
     pub fn clear_icon_path(&mut self) {
         self.splits
             .iter_mut()
@@ -84,6 +102,22 @@ impl Category {
     }
 }
 
+impl Versioned for Category {
+    const MIGRATIONS: &'static [Migration] = &[split_uuids];
+}
+
+/// Splits of unversioned files may have no uuid, which the history relies on.
+fn split_uuids(json: &mut Value) {
+    let Some(splits) = json.get_mut("splits").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for split in splits.iter_mut().filter_map(Value::as_object_mut) {
+        if !split.get("uuid").is_some_and(Value::is_string) {
+            split.insert("uuid".to_owned(), Uuid::new_v4().to_string().into());
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SmallCategory {
     pub uuid: String,
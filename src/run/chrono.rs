@@ -1,5 +1,9 @@
 use std::{
-    fmt::{self, Display},
+    fmt::{self, Display, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -14,64 +18,390 @@ enum ChronometerState {
     Paused,
 }
 
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Copy, Clone, Eq, PartialEq, Deserialize, Serialize)]
-pub enum ChronometerFormat {
-    HHMMSSX,
-    HHMMSS,
-    HHMM,
-    MMSSX,
-    MMSS,
+/// Templates that can be picked from the settings, with their display names.
+pub const FORMAT_PRESETS: [(&str, &str); 6] = [
+    ("H:MM", "{h}:{mm}"),
+    ("H:MM:SS", "{h}:{mm}:{ss}"),
+    ("H:MM:SS.cs", "{h}:{mm}:{ss}.{ff}"),
+    ("[H:]MM:SS.ms", "{h?:}{mm}:{ss}.{fff}"),
+    ("MM:SS", "{mm}:{ss}"),
+    ("MM:SS.cs", "{mm}:{ss}.{ff}"),
+];
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum TimeUnit {
+    Hours,
+    Minutes,
+    Seconds,
+}
+
+impl TimeUnit {
+    fn secs(&self) -> u64 {
+        match self {
+            TimeUnit::Hours => 3600,
+            TimeUnit::Minutes => 60,
+            TimeUnit::Seconds => 1,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+enum FormatToken {
+    Literal(String),
+    Unit {
+        unit: TimeUnit,
+        width: usize,
+        /// Wraps around the next bigger unit present in the template, if any.
+        modulus: Option<u64>,
+        /// When set, the unit and this suffix are only shown if the value is not zero.
+        optional_suffix: Option<String>,
+    },
+    Fraction(u32),
+}
+
+/// Chronometer layout, parsed from a template such as `{h?:}{mm}:{ss}.{fff}`.
+///
+/// Fields are `{h}`, `{m}` and `{s}`, repeated to set the zero padded width, and `{f}`,
+/// `{ff}` or `{fff}` for tenths, hundredths or thousandths of a second. A unit followed by
+/// `?` and a suffix, like `{h?:}`, is hidden together with the suffix while it is zero.
+/// Use `{{` and `}}` for literal braces.
+#[derive(Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ChronometerFormat {
+    template: String,
+    tokens: Vec<FormatToken>,
 }
 
 impl ChronometerFormat {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut tokens: Vec<FormatToken> = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => return Err(format!("Unclosed field \"{{{field}\"")),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        tokens.push(FormatToken::Literal(std::mem::take(&mut literal)));
+                    }
+                    tokens.push(Self::parse_field(&field)?);
+                }
+                '}' => return Err("Unexpected \"}\". Use \"}}\" for a literal brace".to_string()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            tokens.push(FormatToken::Literal(literal));
+        }
+
+        let units: Vec<TimeUnit> = tokens
+            .iter()
+            .filter_map(|t| match t {
+                FormatToken::Unit { unit, .. } => Some(*unit),
+                _ => None,
+            })
+            .collect();
+        if units.is_empty() && !tokens.iter().any(|t| matches!(t, FormatToken::Fraction(_))) {
+            return Err("The format must contain at least one time field".to_string());
+        }
+        tokens.iter_mut().for_each(|t| {
+            if let FormatToken::Unit { unit, modulus, .. } = t {
+                *modulus = units
+                    .iter()
+                    .map(|u| u.secs())
+                    .filter(|secs| *secs > unit.secs())
+                    .min()
+                    .map(|secs| secs / unit.secs());
+            }
+        });
+
+        Ok(Self {
+            template: template.to_owned(),
+            tokens,
+        })
+    }
+
+    fn parse_field(field: &str) -> Result<FormatToken, String> {
+        let (name, optional_suffix) = match field.split_once('?') {
+            Some((name, suffix)) => (name, Some(suffix.to_owned())),
+            None => (field, None),
+        };
+        let width = name.chars().count();
+        let first = match name.chars().next() {
+            Some(c) if name.chars().all(|n| n == c) => c,
+            _ => return Err(format!("Unknown field \"{{{field}}}\"")),
+        };
+        let unit = match first {
+            'h' => TimeUnit::Hours,
+            'm' => TimeUnit::Minutes,
+            's' => TimeUnit::Seconds,
+            'f' if optional_suffix.is_some() => {
+                return Err(format!("Field \"{{{field}}}\" can not be optional"))
+            }
+            'f' if width <= 3 => return Ok(FormatToken::Fraction(width as u32)),
+            'f' => return Err(format!("Field \"{{{field}}}\" allows up to 3 digits")),
+            _ => return Err(format!("Unknown field \"{{{field}}}\"")),
+        };
+        Ok(FormatToken::Unit {
+            unit,
+            width,
+            modulus: None,
+            optional_suffix,
+        })
+    }
+
+    /// Formats stored before templates existed were saved by variant name.
+    fn from_legacy(name: &str) -> Option<&'static str> {
+        match name {
+            "HHMMSSX" => Some("{h}:{mm}:{ss}.{ff}"),
+            "HHMMSS" => Some("{h}:{mm}:{ss}"),
+            "HHMM" => Some("{h}:{mm}"),
+            "MMSSX" => Some("{mm}:{ss}.{ff}"),
+            "MMSS" => Some("{mm}:{ss}"),
+            _ => None,
+        }
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
     pub fn text(&self) -> &str {
-        match self {
-            ChronometerFormat::HHMMSSX => "HH:MM:SS.cs",
-            ChronometerFormat::HHMMSS => "HH:MM:SS",
-            ChronometerFormat::HHMM => "HH:MM",
-            ChronometerFormat::MMSSX => "MM:SS.cs",
-            ChronometerFormat::MMSS => "MM:SS",
+        FORMAT_PRESETS
+            .iter()
+            .find(|(_, template)| *template == self.template)
+            .map(|(name, _)| *name)
+            .unwrap_or(&self.template)
+    }
+}
+
+impl Default for ChronometerFormat {
+    fn default() -> Self {
+        Self::parse(FORMAT_PRESETS[2].1).expect("Default chronometer format must be valid")
+    }
+}
+
+impl TryFrom<String> for ChronometerFormat {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        Self::parse(Self::from_legacy(&template).unwrap_or(&template))
+    }
+}
+
+impl From<ChronometerFormat> for String {
+    fn from(format: ChronometerFormat) -> Self {
+        format.template
+    }
+}
+
+/// A duration that can be negative, used for times that start before zero.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct SignedDuration {
+    pub negative: bool,
+    pub duration: Duration,
+}
+
+impl SignedDuration {
+    /// Values too big for a `Duration`, or not a number, give zero.
+    pub fn from_secs_f64(secs: f64) -> Self {
+        match Duration::try_from_secs_f64(secs.abs()) {
+            Ok(duration) => Self {
+                negative: secs < 0.0,
+                duration,
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn as_secs_f64(&self) -> f64 {
+        if self.negative {
+            -self.duration.as_secs_f64()
+        } else {
+            self.duration.as_secs_f64()
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative && !self.duration.is_zero()
+    }
+
+    pub fn add_duration(self, duration: Duration) -> Self {
+        if !self.negative {
+            Self {
+                negative: false,
+                duration: self.duration + duration,
+            }
+        } else if duration >= self.duration {
+            Self {
+                negative: false,
+                duration: duration - self.duration,
+            }
+        } else {
+            Self {
+                negative: true,
+                duration: self.duration - duration,
+            }
+        }
+    }
+
+    /// Negative durations are clamped to zero.
+    pub fn to_duration(self) -> Duration {
+        if self.negative {
+            Duration::default()
+        } else {
+            self.duration
         }
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+impl From<Duration> for SignedDuration {
+    fn from(duration: Duration) -> Self {
+        Self {
+            negative: false,
+            duration,
+        }
+    }
+}
+
+/// Source of time for the chronometer. Readings are measured from an arbitrary origin
+/// and must never go backwards.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+/// Clock backed by the system monotonic clock.
+pub struct MonotonicClock {
+    origin: Instant,
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// Clock that only moves when told to, for tests and for replaying recorded sessions.
+/// Clones share the same time.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn set(&self, time: Duration) {
+        self.nanos.store(time.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, time: Duration) {
+        self.nanos
+            .fetch_add(time.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+}
+
+#[derive(Clone)]
 pub struct Chronometer {
-    start_time: Option<Instant>,
+    clock: Arc<dyn Clock>,
+    start_time: Option<Duration>,
     elapsed: Option<Duration>,
+    offset: SignedDuration,
+    paused_time: Option<Duration>,
+    paused: Duration,
     state: ChronometerState,
     format: ChronometerFormat,
 }
 
 impl Chronometer {
     pub fn new(format: ChronometerFormat) -> Self {
+        Self::with_clock(format, Arc::new(MonotonicClock::default()))
+    }
+
+    pub fn with_clock(format: ChronometerFormat, clock: Arc<dyn Clock>) -> Self {
         Self {
+            clock,
             start_time: None,
             elapsed: None,
+            offset: SignedDuration::default(),
+            paused_time: None,
+            paused: Duration::default(),
             state: ChronometerState::Stopped,
             format,
         }
     }
 
+    /// Replaces the clock. Only meant to be used while the chronometer is stopped.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    fn since(&self, time: Duration) -> Duration {
+        self.clock.now().saturating_sub(time)
+    }
+
     pub fn set_format(&mut self, format: &ChronometerFormat) {
-        self.format = *format;
+        self.format = format.clone();
     }
 
-    pub fn load_chrono(&mut self, elapsed: Duration, format: &ChronometerFormat) {
+    pub fn load_chrono(&mut self, elapsed: Duration, paused: Duration, format: &ChronometerFormat) {
         self.start_time = None;
         self.elapsed = Some(elapsed);
+        self.offset = SignedDuration::default();
+        self.paused_time = None;
+        self.paused = paused;
         self.state = ChronometerState::Paused;
-        self.format = *format;
+        self.format = format.clone();
     }
 
     pub fn clear_elapsed(&mut self) {
-        self.start_time = Some(Instant::now());
+        self.start_time = Some(self.clock.now());
         self.elapsed = Some(Duration::default());
+        self.offset = SignedDuration::default();
+        self.paused = Duration::default();
+        if self.paused_time.is_some() {
+            self.paused_time = Some(self.clock.now());
+        }
     }
 
-    pub fn start(&mut self) {
-        self.start_time = Some(Instant::now());
+    /// Starts or resumes the chronometer. The offset is only applied when starting
+    /// from a stopped state, so the run may begin at a negative time.
+    pub fn start(&mut self, offset: SignedDuration) {
+        if self.state == ChronometerState::Stopped {
+            self.offset = offset;
+        }
+        if let Some(paused_time) = self.paused_time.take() {
+            self.paused += self.since(paused_time);
+        }
+        self.start_time = Some(self.clock.now());
         self.state = ChronometerState::Running;
     }
 
@@ -79,13 +409,13 @@ impl Chronometer {
         if self.state == ChronometerState::Running {
             let start_time = match self.start_time {
                 Some(st) => st,
-                None => return Err(Error::new(
+                None => return Err(Error::invalid(
                     "Could not pause. Try pressing the \"Start chrono\" key again before pausing."
                         .to_string(),
-                    "None".to_string(),
                 )),
             };
-            self.elapsed = Some(start_time.elapsed() + self.elapsed.unwrap_or_default());
+            self.elapsed = Some(self.since(start_time) + self.elapsed.unwrap_or_default());
+            self.paused_time = Some(self.clock.now());
             self.state = ChronometerState::Paused;
         }
         Ok(())
@@ -95,26 +425,58 @@ impl Chronometer {
         if self.state != ChronometerState::Stopped {
             self.start_time = None;
             self.elapsed = None;
+            self.offset = SignedDuration::default();
+            self.paused_time = None;
+            self.paused = Duration::default();
             self.state = ChronometerState::Stopped;
         }
     }
 
+    /// Time of the current split. Negative times are clamped to zero.
     pub fn get_time(&self) -> Result<Duration, Error> {
+        Ok(self.get_signed_time()?.to_duration())
+    }
+
+    pub fn get_signed_time(&self) -> Result<SignedDuration, Error> {
         let mut time = self.elapsed.unwrap_or_default();
         if self.state == ChronometerState::Running {
             let start_time = match self.start_time {
                 Some(st) => st,
                 None => {
-                    return Err(Error::new(
+                    return Err(Error::invalid(
                         "Could not get time. Try pressing the \"Start chrono\" key again."
                             .to_string(),
-                        "None".to_string(),
                     ))
                 }
             };
-            time += start_time.elapsed();
+            time += self.since(start_time);
+        }
+        Ok(self.offset.add_duration(time))
+    }
+
+    /// Time spent paused during the current split, including an ongoing pause.
+    pub fn get_paused_time(&self) -> Duration {
+        match self.paused_time {
+            Some(paused_time) => self.paused + self.since(paused_time),
+            None => self.paused,
+        }
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.state == ChronometerState::Stopped
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state == ChronometerState::Paused
+    }
+
+    /// Timer phase as named by LiveSplit.
+    pub fn phase(&self) -> &'static str {
+        match self.state {
+            ChronometerState::Stopped => "NotRunning",
+            ChronometerState::Running => "Running",
+            ChronometerState::Paused => "Paused",
         }
-        Ok(time)
     }
 }
 
@@ -123,44 +485,243 @@ impl Display for Chronometer {
         write!(
             f,
             "{}",
-            duration_chrono_format(self.elapsed.unwrap_or_default(), &self.format)
+            duration_chrono_format(
+                self.offset.add_duration(self.elapsed.unwrap_or_default()),
+                &self.format
+            )
         )
     }
 }
 
-pub fn duration_chrono_format(duration: Duration, format: &ChronometerFormat) -> String {
-    let total_millis = duration.as_millis();
-    let total_secs = duration.as_secs();
+pub fn duration_chrono_format(
+    duration: impl Into<SignedDuration>,
+    format: &ChronometerFormat,
+) -> String {
+    let signed: SignedDuration = duration.into();
+    let total_millis = signed.duration.as_millis();
+    let total_secs = signed.duration.as_secs();
 
-    match format {
-        ChronometerFormat::HHMMSSX => {
-            let xs = (total_millis % 1000) / 10;
-            let ss = total_secs % 60;
-            let mm = (total_secs / 60) % 60;
-            let hh = total_secs / 3600;
-            format!("{:}:{:02}:{:02}.{:02}", hh, mm, ss, xs)
-        }
-        ChronometerFormat::HHMMSS => {
-            let ss = total_secs % 60;
-            let mm = (total_secs / 60) % 60;
-            let hh = total_secs / 3600;
-            format!("{:}:{:02}:{:02}", hh, mm, ss)
-        }
-        ChronometerFormat::HHMM => {
-            let mm = (total_secs / 60) % 60;
-            let hh = total_secs / 3600;
-            format!("{:}:{:02}", hh, mm)
+    let mut text = String::with_capacity(format.template.len() + 4);
+    if signed.is_negative() {
+        text.push('-');
+    }
+    for token in format.tokens.iter() {
+        match token {
+            FormatToken::Literal(literal) => text.push_str(literal),
+            FormatToken::Unit {
+                unit,
+                width,
+                modulus,
+                optional_suffix,
+            } => {
+                let mut value = total_secs / unit.secs();
+                if let Some(m) = modulus {
+                    value %= m;
+                }
+                match optional_suffix {
+                    Some(_) if value == 0 => (),
+                    _ => {
+                        let _ = write!(text, "{value:0width$}");
+                        if let Some(suffix) = optional_suffix {
+                            text.push_str(suffix);
+                        }
+                    }
+                }
+            }
+            FormatToken::Fraction(digits) => {
+                let value = (total_millis % 1000) / 10u128.pow(3 - digits);
+                let _ = write!(text, "{value:0width$}", width = *digits as usize);
+            }
         }
-        ChronometerFormat::MMSSX => {
-            let xs = (total_millis % 1000) / 10;
-            let ss = total_secs % 60;
-            let mm = total_secs / 60;
-            format!("{:02}:{:02}.{:02}", mm, ss, xs)
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(template: &str) -> ChronometerFormat {
+        ChronometerFormat::parse(template).unwrap()
+    }
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    fn manual_chrono() -> (Chronometer, ManualClock) {
+        let clock = ManualClock::default();
+        let chrono = Chronometer::with_clock(Default::default(), Arc::new(clock.clone()));
+        (chrono, clock)
+    }
+
+    #[test]
+    fn parses_templates() {
+        let f = format("{h}:{mm}:{ss}.{fff}");
+        assert_eq!(f.template(), "{h}:{mm}:{ss}.{fff}");
+        assert_eq!(format("{h}:{mm}:{ss}.{ff}").text(), "H:MM:SS.cs");
+        assert_eq!(format("{ss}").text(), "{ss}");
+        assert_eq!(duration_chrono_format(secs(5.0), &format("{{{s}}}")), "{5}");
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        for template in [
+            "", "time", "{h", "{h}}", "}", "{x}", "{hm}", "{ffff}", "{f?:}",
+        ] {
+            assert!(
+                ChronometerFormat::parse(template).is_err(),
+                "{template} should be rejected"
+            );
         }
-        ChronometerFormat::MMSS => {
-            let ss = total_secs % 60;
-            let mm = total_secs / 60;
-            format!("{:02}:{:02}", mm, ss)
+    }
+
+    #[test]
+    fn migrates_legacy_formats() {
+        for (legacy, template) in [
+            ("HHMMSSX", "{h}:{mm}:{ss}.{ff}"),
+            ("HHMMSS", "{h}:{mm}:{ss}"),
+            ("HHMM", "{h}:{mm}"),
+            ("MMSSX", "{mm}:{ss}.{ff}"),
+            ("MMSS", "{mm}:{ss}"),
+        ] {
+            let f: ChronometerFormat = serde_json::from_str(&format!("\"{legacy}\"")).unwrap();
+            assert_eq!(f.template(), template);
         }
+        assert!(serde_json::from_str::<ChronometerFormat>("\"HHMMSSXX\"").is_err());
+    }
+
+    #[test]
+    fn formats_hour_rollover() {
+        let f = format("{h}:{mm}:{ss}.{ff}");
+        assert_eq!(duration_chrono_format(secs(3599.999), &f), "0:59:59.99");
+        assert_eq!(duration_chrono_format(secs(3600.0), &f), "1:00:00.00");
+        assert_eq!(duration_chrono_format(secs(90061.5), &f), "25:01:01.50");
+        // Without hours in the template, minutes keep counting past an hour.
+        assert_eq!(
+            duration_chrono_format(secs(3661.0), &format("{mm}:{ss}")),
+            "61:01"
+        );
+    }
+
+    #[test]
+    fn hides_optional_hours() {
+        let f = format("{h?:}{mm}:{ss}.{fff}");
+        assert_eq!(duration_chrono_format(secs(59.5), &f), "00:59.500");
+        assert_eq!(duration_chrono_format(secs(3600.25), &f), "1:00:00.250");
+    }
+
+    #[test]
+    fn formats_fractions() {
+        let d = secs(1.2345);
+        assert_eq!(duration_chrono_format(d, &format("{s}.{f}")), "1.2");
+        assert_eq!(duration_chrono_format(d, &format("{s}.{ff}")), "1.23");
+        assert_eq!(duration_chrono_format(d, &format("{s}.{fff}")), "1.234");
+    }
+
+    #[test]
+    fn formats_negative_times() {
+        let f = format("{ss}.{ff}");
+        assert_eq!(
+            duration_chrono_format(SignedDuration::from_secs_f64(-1.5), &f),
+            "-01.50"
+        );
+        assert_eq!(
+            duration_chrono_format(SignedDuration::from_secs_f64(-0.0), &f),
+            "00.00"
+        );
+    }
+
+    #[test]
+    fn signed_duration_from_out_of_range_secs() {
+        assert!(SignedDuration::from_secs_f64(1e30) == SignedDuration::default());
+        assert!(SignedDuration::from_secs_f64(-1e30) == SignedDuration::default());
+        assert!(SignedDuration::from_secs_f64(f64::NAN) == SignedDuration::default());
+        assert_eq!(SignedDuration::from_secs_f64(-2.0).as_secs_f64(), -2.0);
+    }
+
+    #[test]
+    fn runs_with_manual_clock() {
+        let (mut chrono, clock) = manual_chrono();
+        assert!(chrono.is_stopped());
+        chrono.start(SignedDuration::default());
+        clock.advance(secs(10.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(10.0));
+        assert_eq!(chrono.phase(), "Running");
+    }
+
+    #[test]
+    fn pauses_and_resumes() {
+        let (mut chrono, clock) = manual_chrono();
+        chrono.start(SignedDuration::default());
+        clock.advance(secs(10.0));
+        chrono.pause().unwrap();
+        clock.advance(secs(5.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(10.0));
+        assert_eq!(chrono.get_paused_time(), secs(5.0));
+        assert_eq!(chrono.phase(), "Paused");
+
+        chrono.start(SignedDuration::default());
+        clock.advance(secs(2.0));
+        chrono.pause().unwrap();
+        clock.advance(secs(1.0));
+        chrono.start(SignedDuration::default());
+        clock.advance(secs(3.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(15.0));
+        assert_eq!(chrono.get_paused_time(), secs(6.0));
+
+        // Pausing twice does not count the time in between.
+        chrono.pause().unwrap();
+        clock.advance(secs(1.0));
+        chrono.pause().unwrap();
+        assert_eq!(chrono.get_time().unwrap(), secs(15.0));
+    }
+
+    #[test]
+    fn starts_at_negative_offset() {
+        let (mut chrono, clock) = manual_chrono();
+        chrono.start(SignedDuration::from_secs_f64(-1.5));
+        clock.advance(secs(1.0));
+        assert_eq!(chrono.get_signed_time().unwrap().as_secs_f64(), -0.5);
+        assert_eq!(chrono.get_time().unwrap(), Duration::default());
+        assert_eq!(
+            duration_chrono_format(chrono.get_signed_time().unwrap(), &chrono.format),
+            "-0:00:00.50"
+        );
+
+        clock.advance(secs(1.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(0.5));
+
+        // The offset is only applied when starting from a stopped state.
+        chrono.pause().unwrap();
+        chrono.start(SignedDuration::from_secs_f64(-10.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(0.5));
+    }
+
+    #[test]
+    fn clears_and_resets() {
+        let (mut chrono, clock) = manual_chrono();
+        chrono.start(SignedDuration::from_secs_f64(-1.0));
+        clock.advance(secs(4.0));
+        chrono.clear_elapsed();
+        clock.advance(secs(2.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(2.0));
+
+        chrono.reset();
+        assert!(chrono.is_stopped());
+        assert_eq!(chrono.get_time().unwrap(), Duration::default());
+        assert_eq!(chrono.phase(), "NotRunning");
+    }
+
+    #[test]
+    fn loads_stored_time() {
+        let (mut chrono, clock) = manual_chrono();
+        chrono.load_chrono(secs(7.0), secs(1.0), &format("{s}"));
+        clock.advance(secs(3.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(7.0));
+        assert_eq!(chrono.to_string(), "7");
+        chrono.start(SignedDuration::default());
+        clock.advance(secs(3.0));
+        assert_eq!(chrono.get_time().unwrap(), secs(10.0));
     }
 }
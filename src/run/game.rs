@@ -2,7 +2,12 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{get_config_path, Error};
+use crate::{
+    schema::{self, unversioned, Migration, Versioned},
+    settings::shortcut::Shortcut,
+    storage::{self, Record},
+    Error,
+};
 
 use super::category::SmallCategory;
 
@@ -12,6 +17,9 @@ pub struct Game {
     pub name: String,
     pub icon_path: Option<PathBuf>,
     pub categories: Vec<SmallCategory>,
+    /// Bindings used instead of those of the settings while it is loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcut_overrides: Option<Shortcut>,
 }
 
 impl Game {
@@ -28,11 +36,10 @@ impl Game {
     }
 
     pub fn save(&self) -> Result<(), Error> {
-        let config_path = get_config_path();
-        let game_str = match serde_json::to_string(&self) {
+        let game_str = match schema::to_string(self) {
             Ok(game) => game,
             Err(e) => {
-                return Err(Error::new(
+                return Err(Error::parse(
                     format!(
                         "Could not serialize game {} with uuid {}",
                         self.name, self.uuid
@@ -41,38 +48,34 @@ impl Game {
                 ))
             }
         };
-        match std::fs::write(format!("{config_path}/games/{}.json", self.uuid), game_str) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(Error::new(
-                format!(
-                    "Could not save game with uuid {} on path \"{config_path}/games/{}.json\"",
-                    self.uuid, self.uuid
-                ),
-                e.to_string(),
-            )),
-        }
+        storage::write(&Record::Game(self.uuid.clone()), &game_str)
     }
 
     pub fn load(uuid: String) -> Result<Self, Error> {
-        let config_path = get_config_path();
-        let game_json: String =
-            match std::fs::read_to_string(format!("{config_path}/games/{uuid}.json")) {
-                Err(_) => "".to_string(),
-                Ok(f) => f,
-            };
+        let record = Record::Game(uuid.clone());
+        let game_json: String = match storage::read(&record)? {
+            None => {
+                return Err(Error::missing(format!(
+                    "Could not load game with uuid {uuid}. Not found: {record}"
+                )))
+            }
+            Some(f) => f,
+        };
 
-        match serde_json::from_str(game_json.as_str()) {
+        match schema::from_str(game_json.as_str()) {
             Ok(category) => Ok(category),
-            Err(e) => Err(Error::new(
-                format!(
-                    "Could not parse category json file: \"{config_path}/categories/{uuid}.json\""
-                ),
+            Err(e) => Err(Error::parse(
+                format!("Could not parse game {record}"),
                 e.to_string(),
             )),
         }
     }
 }
 
+impl Versioned for Game {
+    const MIGRATIONS: &'static [Migration] = &[unversioned];
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SmallGame {
     pub uuid: String,
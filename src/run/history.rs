@@ -0,0 +1,133 @@
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    schema::{self, unversioned, Migration, Versioned},
+    storage::{self, Record},
+    Error,
+};
+
+use super::{category::Category, split::Split};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AttemptSplit {
+    pub uuid: Option<String>,
+    pub name: String,
+    pub hits: u16,
+    pub real_time: Duration,
+    pub paused_time: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Attempt {
+    pub started_at: Option<SystemTime>,
+    pub ended_at: SystemTime,
+    /// Whether the last split was finished, instead of reset halfway through.
+    #[serde(default)]
+    pub completed: bool,
+    /// Splits reached during the attempt, in order.
+    pub splits: Vec<AttemptSplit>,
+}
+
+impl Attempt {
+    pub fn new(
+        category: &Category,
+        reached: usize,
+        started_at: Option<SystemTime>,
+        completed: bool,
+    ) -> Self {
+        Self {
+            started_at,
+            ended_at: SystemTime::now(),
+            completed,
+            splits: category
+                .splits
+                .iter()
+                .take(reached)
+                .map(|split| AttemptSplit {
+                    uuid: split.uuid.clone(),
+                    name: split.name.clone(),
+                    hits: split.hits,
+                    real_time: split.real_time,
+                    paused_time: split.paused_time,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Finished attempts of a category. Stored apart from the category so it can grow
+/// without slowing down category saves.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct History {
+    pub category_uuid: String,
+    pub attempts: Vec<Attempt>,
+}
+
+impl History {
+    pub fn load(uuid: String) -> Result<Self, Error> {
+        let record = Record::History(uuid.clone());
+        let history_json: String = match storage::read(&record)? {
+            None => {
+                return Ok(History {
+                    category_uuid: uuid,
+                    ..Default::default()
+                })
+            }
+            Some(f) => f,
+        };
+
+        match schema::from_str(history_json.as_str()) {
+            Ok(history) => Ok(history),
+            Err(e) => Err(Error::parse(
+                format!("Could not parse history {record}"),
+                e.to_string(),
+            )),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let history_str = match schema::to_string(self) {
+            Ok(history) => history,
+            Err(e) => {
+                return Err(Error::parse(
+                    format!(
+                        "Could not serialize history of category with uuid {}",
+                        self.category_uuid
+                    ),
+                    e.to_string(),
+                ))
+            }
+        };
+        storage::write(&Record::History(self.category_uuid.clone()), &history_str)
+    }
+
+    /// Best known time of `split`: its fastest finished time across all attempts, its PB
+    /// time or the best time imported from other tools.
+    pub fn best_time(&self, split: &Split) -> Option<Duration> {
+        let uuid = split.uuid.as_deref()?;
+        self.attempts
+            .iter()
+            .flat_map(|attempt| {
+                let finished = match attempt.completed {
+                    true => attempt.splits.len(),
+                    false => attempt.splits.len().saturating_sub(1),
+                };
+                attempt.splits.iter().take(finished)
+            })
+            .filter(|split| split.uuid.as_deref() == Some(uuid) && !split.real_time.is_zero())
+            .map(|split| split.real_time)
+            .chain(split.pb_time)
+            .chain(split.gold_time)
+            .min()
+    }
+
+    pub fn record(category_uuid: &str, attempt: Attempt) -> Result<(), Error> {
+        storage::storage().record_attempt(category_uuid, &attempt)
+    }
+}
+
+impl Versioned for History {
+    const MIGRATIONS: &'static [Migration] = &[unversioned];
+}
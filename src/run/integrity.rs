@@ -0,0 +1,434 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use uuid::Uuid;
+
+use crate::{
+    logging,
+    schema::{self, Versioned},
+    settings::config::Config,
+    storage::{self, Record},
+    Error,
+};
+
+use super::{
+    assets,
+    category::{Category, SmallCategory},
+    game::{Game, SmallGame},
+};
+
+/// Game that categories no game refers to are added to when repaired.
+const RECOVERED_GAME: &str = "Recovered categories";
+
+/// Inconsistency found in the stored data by `check`.
+#[derive(Clone)]
+pub enum Problem {
+    /// A record that can not be parsed. It can only be fixed by hand or from a backup.
+    Unreadable { record: Record, error: String },
+    /// An entry of the game list whose game does not exist.
+    MissingGame { uuid: String, name: String },
+    /// A stored game that is not in the game list.
+    UnlistedGame { uuid: String, name: String },
+    /// A game that is in the game list more than once.
+    DuplicateGame { uuid: String, name: String },
+    /// A category entry of a game whose category does not exist.
+    MissingCategory {
+        game: String,
+        game_name: String,
+        uuid: String,
+        name: String,
+    },
+    /// A category entry repeated in the same game or, if `shared`, found in another game.
+    DuplicateCategory {
+        game: String,
+        game_name: String,
+        uuid: String,
+        name: String,
+        shared: bool,
+    },
+    /// A stored category that no game refers to.
+    OrphanCategory { uuid: String, name: String },
+    /// A history whose category does not exist.
+    OrphanHistory { uuid: String },
+    /// Splits of a category that share a uuid.
+    DuplicateSplit {
+        category: String,
+        category_name: String,
+        uuid: String,
+    },
+    /// A game or category whose uuid is not the one it is stored under.
+    UuidMismatch { record: Record, uuid: String },
+    /// An icon of a game or category whose image does not exist.
+    BrokenImage { record: Record, path: PathBuf },
+}
+
+impl Problem {
+    pub fn description(&self) -> String {
+        match self {
+            Problem::Unreadable { record, error } => format!("{record} can not be read: {error}"),
+            Problem::MissingGame { name, .. } => {
+                format!("Game \"{name}\" is in the game list, but its file is missing")
+            }
+            Problem::UnlistedGame { name, .. } => {
+                format!("Game \"{name}\" is not in the game list")
+            }
+            Problem::DuplicateGame { name, .. } => {
+                format!("Game \"{name}\" is in the game list more than once")
+            }
+            Problem::MissingCategory {
+                game_name, name, ..
+            } => format!("Category \"{name}\" of game \"{game_name}\" is missing"),
+            Problem::DuplicateCategory {
+                game_name,
+                name,
+                shared: false,
+                ..
+            } => format!("Category \"{name}\" is in game \"{game_name}\" more than once"),
+            Problem::DuplicateCategory {
+                game_name,
+                name,
+                shared: true,
+                ..
+            } => format!("Category \"{name}\" of game \"{game_name}\" is shared with another game"),
+            Problem::OrphanCategory { name, .. } => {
+                format!("Category \"{name}\" does not belong to any game")
+            }
+            Problem::OrphanHistory { uuid } => {
+                format!("History of category {uuid} has no category")
+            }
+            Problem::DuplicateSplit {
+                category_name,
+                uuid,
+                ..
+            } => format!("Several splits of category \"{category_name}\" have the uuid {uuid}"),
+            Problem::UuidMismatch { record, uuid } => {
+                format!("{record} has the uuid {uuid} instead of its file name")
+            }
+            Problem::BrokenImage { record, path } => {
+                format!("Image \"{}\" of {record} does not exist", path.display())
+            }
+        }
+    }
+
+    pub fn fixable(&self) -> bool {
+        !matches!(self, Problem::Unreadable { .. })
+    }
+
+    /// Fixes the problem in the stored data. Dangling references and broken images are
+    /// removed, unlisted games listed, orphan categories moved to a recovery game,
+    /// orphan histories deleted and duplicates given new uuids.
+    pub fn repair(&self) -> Result<(), Error> {
+        self.fix()?;
+        if self.fixable() {
+            logging::info(&format!("Fixed: {}", self.description()));
+        }
+        Ok(())
+    }
+
+    fn fix(&self) -> Result<(), Error> {
+        match self {
+            Problem::Unreadable { .. } => Ok(()),
+            Problem::MissingGame { uuid, .. } => {
+                let mut config = Config::load()?;
+                config.game_list.retain(|g| g.uuid != *uuid);
+                config.save()
+            }
+            Problem::UnlistedGame { uuid, name } => {
+                let mut config = Config::load()?;
+                if !config.game_list.iter().any(|g| g.uuid == *uuid) {
+                    config.game_list.push(SmallGame {
+                        uuid: uuid.clone(),
+                        name: name.clone(),
+                    });
+                }
+                config.save()
+            }
+            Problem::DuplicateGame { uuid, .. } => {
+                let mut config = Config::load()?;
+                let mut seen = false;
+                config
+                    .game_list
+                    .retain(|g| g.uuid != *uuid || !std::mem::replace(&mut seen, true));
+                config.save()
+            }
+            Problem::MissingCategory { game, uuid, .. } => {
+                let mut game = Game::load(game.clone())?;
+                game.categories.retain(|c| c.uuid != *uuid);
+                game.save()
+            }
+            Problem::DuplicateCategory {
+                game, uuid, shared, ..
+            } => {
+                let mut game = Game::load(game.clone())?;
+                if *shared {
+                    let Some(entry) = game.categories.iter_mut().find(|c| c.uuid == *uuid) else {
+                        return Ok(());
+                    };
+                    let mut copy = Category::load(uuid.clone())?;
+                    copy.uuid = Uuid::new_v4().to_string();
+                    copy.save()?;
+                    entry.uuid.clone_from(&copy.uuid);
+                } else {
+                    let mut seen = false;
+                    game.categories
+                        .retain(|c| c.uuid != *uuid || !std::mem::replace(&mut seen, true));
+                }
+                game.save()
+            }
+            Problem::OrphanCategory { uuid, name } => {
+                let mut config = Config::load()?;
+                let mut game = match config.game_list.iter().find(|g| g.name == RECOVERED_GAME) {
+                    Some(g) => Game::load(g.uuid.clone())?,
+                    None => {
+                        let game = Game::new(Uuid::new_v4().to_string(), RECOVERED_GAME.to_owned());
+                        config.game_list.push(SmallGame {
+                            uuid: game.uuid.clone(),
+                            name: game.name.clone(),
+                        });
+                        config.save()?;
+                        game
+                    }
+                };
+                game.categories.push(SmallCategory {
+                    uuid: uuid.clone(),
+                    name: name.clone(),
+                });
+                game.save()
+            }
+            Problem::OrphanHistory { uuid } => storage::delete(&Record::History(uuid.clone())),
+            Problem::DuplicateSplit { category, uuid, .. } => {
+                let mut category = Category::load(category.clone())?;
+                category
+                    .splits
+                    .iter_mut()
+                    .filter(|s| s.uuid.as_deref() == Some(uuid.as_str()))
+                    .skip(1)
+                    .for_each(|s| s.uuid = Some(Uuid::new_v4().to_string()));
+                category.save()
+            }
+            Problem::UuidMismatch { record, .. } => match record {
+                Record::Game(uuid) => {
+                    let mut game = Game::load(uuid.clone())?;
+                    game.uuid.clone_from(uuid);
+                    game.save()
+                }
+                Record::Category(uuid) => {
+                    let mut category = Category::load(uuid.clone())?;
+                    category.uuid.clone_from(uuid);
+                    category.save()
+                }
+                _ => Ok(()),
+            },
+            Problem::BrokenImage { record, path } => {
+                let clear = |icon_path: &mut Option<PathBuf>| {
+                    if icon_path.as_ref() == Some(path) {
+                        *icon_path = None;
+                    }
+                };
+                match record {
+                    Record::Game(uuid) => {
+                        let mut game = Game::load(uuid.clone())?;
+                        clear(&mut game.icon_path);
+                        game.save()
+                    }
+                    Record::Category(uuid) => {
+                        let mut category = Category::load(uuid.clone())?;
+                        clear(&mut category.icon_path);
+                        category
+                            .splits
+                            .iter_mut()
+                            .for_each(|s| clear(&mut s.icon_path));
+                        category.save()
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Parses a stored record. The inner error holds the reason it can not be parsed.
+fn load<T: Versioned>(record: &Record) -> Result<Option<Result<T, String>>, Error> {
+    Ok(storage::read(record)?.map(|json| schema::from_str(&json).map_err(|e| e.to_string())))
+}
+
+fn broken_image(record: &Record, icon_path: &Option<PathBuf>) -> Option<Problem> {
+    let path = icon_path.as_ref()?;
+    (!assets::resolve(path).exists()).then(|| Problem::BrokenImage {
+        record: record.clone(),
+        path: path.clone(),
+    })
+}
+
+/// Looks for inconsistencies between the game list, the games, the categories and their
+/// histories and images.
+pub fn check() -> Result<Vec<Problem>, Error> {
+    let mut problems = Vec::new();
+    let config = Config::load()?;
+    let mut games: HashMap<String, Game> = HashMap::new();
+    let mut categories: HashMap<String, Category> = HashMap::new();
+    let mut histories = Vec::new();
+    let mut unreadable = HashSet::new();
+
+    let mut records = storage::storage().records()?;
+    records.sort_by_key(|record| record.file());
+    for record in records {
+        match &record {
+            Record::Game(uuid) => match load::<Game>(&record)? {
+                Some(Ok(game)) => {
+                    games.insert(uuid.clone(), game);
+                }
+                Some(Err(error)) => problems.push(Problem::Unreadable { record, error }),
+                None => (),
+            },
+            Record::Category(uuid) => match load::<Category>(&record)? {
+                Some(Ok(category)) => {
+                    categories.insert(uuid.clone(), category);
+                }
+                Some(Err(error)) => problems.push(Problem::Unreadable { record, error }),
+                None => (),
+            },
+            Record::History(uuid) => histories.push(uuid.clone()),
+            _ => (),
+        }
+    }
+    for problem in problems.iter() {
+        if let Problem::Unreadable { record, .. } = problem {
+            unreadable.insert(record.clone());
+        }
+    }
+
+    let mut listed = HashSet::new();
+    for small in config.game_list.iter() {
+        if !listed.insert(small.uuid.clone()) {
+            problems.push(Problem::DuplicateGame {
+                uuid: small.uuid.clone(),
+                name: small.name.clone(),
+            });
+        } else if !games.contains_key(&small.uuid)
+            && !unreadable.contains(&Record::Game(small.uuid.clone()))
+        {
+            problems.push(Problem::MissingGame {
+                uuid: small.uuid.clone(),
+                name: small.name.clone(),
+            });
+        }
+    }
+
+    // Listed games come first, so the category entries of unlisted games are the ones
+    // reported as duplicates.
+    let mut ordered: Vec<(&String, &Game)> = config
+        .game_list
+        .iter()
+        .filter_map(|g| games.get_key_value(&g.uuid))
+        .collect();
+    let mut unlisted: Vec<(&String, &Game)> = games
+        .iter()
+        .filter(|(uuid, _)| !listed.contains(*uuid))
+        .collect();
+    unlisted.sort_by_key(|(uuid, _)| *uuid);
+    for (uuid, game) in unlisted.iter() {
+        problems.push(Problem::UnlistedGame {
+            uuid: (*uuid).clone(),
+            name: game.name.clone(),
+        });
+    }
+    ordered.extend(unlisted);
+
+    let mut visited = HashSet::new();
+    let mut referenced: HashMap<&String, &String> = HashMap::new();
+    // Shared categories are fixed by copying them, so they go last for the copies to
+    // include the fixes of the category.
+    let mut shared = Vec::new();
+    for (uuid, game) in ordered {
+        if !visited.insert(uuid) {
+            continue;
+        }
+        let record = Record::Game(uuid.clone());
+        if game.uuid != *uuid {
+            problems.push(Problem::UuidMismatch {
+                record: record.clone(),
+                uuid: game.uuid.clone(),
+            });
+        }
+        problems.extend(broken_image(&record, &game.icon_path));
+        for small in game.categories.iter() {
+            if let Some(first_game) = referenced.get(&small.uuid) {
+                let is_shared = *first_game != uuid;
+                let problem = Problem::DuplicateCategory {
+                    game: uuid.clone(),
+                    game_name: game.name.clone(),
+                    uuid: small.uuid.clone(),
+                    name: small.name.clone(),
+                    shared: is_shared,
+                };
+                if is_shared {
+                    shared.push(problem);
+                } else {
+                    problems.push(problem);
+                }
+                continue;
+            }
+            referenced.insert(&small.uuid, uuid);
+            if !categories.contains_key(&small.uuid)
+                && !unreadable.contains(&Record::Category(small.uuid.clone()))
+            {
+                problems.push(Problem::MissingCategory {
+                    game: uuid.clone(),
+                    game_name: game.name.clone(),
+                    uuid: small.uuid.clone(),
+                    name: small.name.clone(),
+                });
+            }
+        }
+    }
+
+    let mut sorted: Vec<(&String, &Category)> = categories.iter().collect();
+    sorted.sort_by_key(|(uuid, _)| *uuid);
+    for (uuid, category) in sorted {
+        let record = Record::Category(uuid.clone());
+        if category.uuid != *uuid {
+            problems.push(Problem::UuidMismatch {
+                record: record.clone(),
+                uuid: category.uuid.clone(),
+            });
+        }
+        if !referenced.contains_key(uuid) {
+            problems.push(Problem::OrphanCategory {
+                uuid: uuid.clone(),
+                name: category.name.clone(),
+            });
+        }
+        let mut split_uuids = HashSet::new();
+        let mut reported = HashSet::new();
+        for split in category.splits.iter() {
+            if let Some(split_uuid) = &split.uuid {
+                if !split_uuids.insert(split_uuid) && reported.insert(split_uuid) {
+                    problems.push(Problem::DuplicateSplit {
+                        category: uuid.clone(),
+                        category_name: category.name.clone(),
+                        uuid: split_uuid.clone(),
+                    });
+                }
+            }
+        }
+        problems.extend(broken_image(&record, &category.icon_path));
+        problems.extend(
+            category
+                .splits
+                .iter()
+                .filter_map(|split| broken_image(&record, &split.icon_path)),
+        );
+    }
+
+    for uuid in histories {
+        if !categories.contains_key(&uuid) && !unreadable.contains(&Record::Category(uuid.clone()))
+        {
+            problems.push(Problem::OrphanHistory { uuid });
+        }
+    }
+    problems.extend(shared);
+    Ok(problems)
+}
@@ -1,29 +1,27 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use crate::{Error, HitSplit};
 
+use super::{
+    history::{Attempt, History},
+    split::Split,
+};
+
 pub fn prev_split(app: &mut HitSplit) -> Result<(), Error> {
     if app.selected_split > 0 {
         app.selected_split -= 1;
         let split = match &app.loaded_category.as_ref() {
             Some(c) => match c.splits.get(app.selected_split) {
                 Some(split) => split,
-                None => {
-                    return Err(Error::new(
-                        "Split not selected".to_string(),
-                        "None".to_string(),
-                    ))
-                }
+                None => return Err(Error::missing("Split not selected".to_string())),
             },
-            None => {
-                return Err(Error::new(
-                    "Category not loaded".to_string(),
-                    "None".to_string(),
-                ))
-            }
+            None => return Err(Error::missing("Category not loaded".to_string())),
         };
-        app.chrono
-            .load_chrono(split.real_time, &app.config.chrono_format);
+        app.chrono.load_chrono(
+            split.real_time,
+            split.paused_time,
+            &app.config.chrono_format,
+        );
     }
     Ok(())
 }
@@ -34,13 +32,12 @@ pub fn next_split(app: &mut HitSplit) -> Result<(), Error> {
             app.selected_split += 1;
             app.chrono.clear_elapsed();
         } else if app.config.next_split_as_reset {
-            app.selected_split = 0;
             let pbs = category.splits.iter().map(|split| split.pb).sum::<u16>();
-            let hits = category.splits.iter().map(|split| split.hits).sum::<u16>();
+            let hits = category.splits.iter().map(Split::total_hits).sum::<u16>();
             if pbs > hits {
                 set_pb(app)?;
             }
-            reset(app)?;
+            end_attempt(app, true)?;
         }
     }
     Ok(())
@@ -51,9 +48,8 @@ pub fn sub_hit(app: &mut HitSplit) -> Result<(), Error> {
         let split = match category.splits.get_mut(app.selected_split) {
             Some(s) => s,
             None => {
-                return Err(Error::new(
+                return Err(Error::missing(
                     "Could not substract hit. Split not selected.".to_string(),
-                    "None".to_string(),
                 ))
             }
         };
@@ -69,9 +65,8 @@ pub fn add_hit(app: &mut HitSplit) -> Result<(), Error> {
         match category.splits.get_mut(app.selected_split) {
             Some(split) => split.hits += 1,
             None => {
-                return Err(Error::new(
+                return Err(Error::missing(
                     "Could not add hit. Split not selected.".to_string(),
-                    "None".to_string(),
                 ))
             }
         };
@@ -80,31 +75,263 @@ pub fn add_hit(app: &mut HitSplit) -> Result<(), Error> {
 }
 
 pub fn reset(app: &mut HitSplit) -> Result<(), Error> {
+    end_attempt(app, false)
+}
+
+fn end_attempt(app: &mut HitSplit, completed: bool) -> Result<(), Error> {
+    let started_at = app.attempt_started.take();
+    let mut result = Ok(());
     if let Some(category) = app.loaded_category.as_mut() {
+        if !app.chrono.is_stopped() || category.splits.iter().any(|split| split.total_hits() > 0) {
+            let attempt = Attempt::new(category, app.selected_split + 1, started_at, completed);
+            result = History::record(&category.uuid, attempt);
+        }
         category.splits.iter_mut().for_each(|split| {
             split.hits = 0;
+            split.way_hits = 0;
             split.real_time = Duration::default();
+            split.paused_time = Duration::default();
         });
         app.selected_split = 0;
         app.chrono.reset();
     }
-    Ok(())
+    result
 }
 
 pub fn set_pb(app: &mut HitSplit) -> Result<(), Error> {
     if let Some(category) = app.loaded_category.as_mut() {
         category.splits.iter_mut().for_each(|split| {
-            split.pb = split.hits;
+            split.pb = split.total_hits();
+            if !split.real_time.is_zero() {
+                split.pb_time = Some(split.real_time);
+            }
         });
     }
     Ok(())
 }
 
 pub fn start_chrono(app: &mut HitSplit) -> Result<(), Error> {
-    app.chrono.start();
+    let offset = match &app.loaded_category {
+        Some(category) => category.start_offset,
+        None => Default::default(),
+    };
+    if app.chrono.is_stopped() {
+        app.attempt_started = Some(SystemTime::now());
+    }
+    app.chrono.start(offset);
+    Ok(())
+}
+
+/// Copies the time of the chronometer to the selected split. Called on every frame.
+pub fn store_split_time(app: &mut HitSplit) -> Result<(), Error> {
+    if let Some(category) = app.loaded_category.as_mut() {
+        if let Some(split) = category.splits.get_mut(app.selected_split) {
+            split.real_time = app.chrono.get_time()?;
+            split.paused_time = app.chrono.get_paused_time();
+        }
+    }
     Ok(())
 }
 
 pub fn pause_chrono(app: &mut HitSplit) -> Result<(), Error> {
     app.chrono.pause()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        run::{
+            category::Category,
+            chrono::{Chronometer, SignedDuration},
+        },
+        storage, ManualClock,
+    };
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    /// App with a category of three splits, timed by the returned clock.
+    fn app() -> (HitSplit, ManualClock) {
+        storage::use_test_storage();
+        let clock = ManualClock::default();
+        let mut category = Category::new(Uuid::new_v4().to_string(), "Any%".to_string());
+        category.splits = ["First", "Second", "Last"]
+            .iter()
+            .map(|name| Split {
+                name: name.to_string(),
+                ..Split::new(Some(Uuid::new_v4().to_string()))
+            })
+            .collect();
+        let app = HitSplit {
+            loaded_category: Some(category),
+            chrono: Chronometer::with_clock(Default::default(), Arc::new(clock.clone())),
+            ..Default::default()
+        };
+        (app, clock)
+    }
+
+    /// Moves the clock and stores the time in the selected split, as every frame does.
+    fn advance(app: &mut HitSplit, clock: &ManualClock, time: Duration) {
+        clock.advance(time);
+        store_split_time(app).unwrap();
+    }
+
+    fn splits(app: &HitSplit) -> &[Split] {
+        &app.loaded_category.as_ref().unwrap().splits
+    }
+
+    fn history(app: &HitSplit) -> History {
+        History::load(app.loaded_category.as_ref().unwrap().uuid.clone()).unwrap()
+    }
+
+    #[test]
+    fn adds_and_substracts_hits() {
+        let (mut app, _) = app();
+        add_hit(&mut app).unwrap();
+        add_hit(&mut app).unwrap();
+        sub_hit(&mut app).unwrap();
+        assert_eq!(splits(&app)[0].hits, 1);
+        sub_hit(&mut app).unwrap();
+        sub_hit(&mut app).unwrap();
+        assert_eq!(splits(&app)[0].hits, 0);
+    }
+
+    #[test]
+    fn hits_without_category_do_nothing() {
+        let mut app = HitSplit::default();
+        assert!(add_hit(&mut app).is_ok());
+        assert!(sub_hit(&mut app).is_ok());
+    }
+
+    #[test]
+    fn moves_between_splits() {
+        let (mut app, clock) = app();
+        start_chrono(&mut app).unwrap();
+        advance(&mut app, &clock, secs(10));
+        next_split(&mut app).unwrap();
+        assert_eq!(app.selected_split, 1);
+        advance(&mut app, &clock, secs(4));
+        assert_eq!(splits(&app)[0].real_time, secs(10));
+        assert_eq!(splits(&app)[1].real_time, secs(4));
+
+        // Going back loads the time of the previous split, paused.
+        prev_split(&mut app).unwrap();
+        assert_eq!(app.selected_split, 0);
+        advance(&mut app, &clock, secs(5));
+        assert_eq!(splits(&app)[0].real_time, secs(10));
+        assert_eq!(app.chrono.phase(), "Paused");
+
+        prev_split(&mut app).unwrap();
+        assert_eq!(app.selected_split, 0);
+    }
+
+    #[test]
+    fn next_split_on_last_split_ends_attempt() {
+        let (mut app, clock) = app();
+        app.loaded_category.as_mut().unwrap().splits[0].pb = 5;
+        start_chrono(&mut app).unwrap();
+        add_hit(&mut app).unwrap();
+        for _ in 0..3 {
+            advance(&mut app, &clock, secs(2));
+            next_split(&mut app).unwrap();
+        }
+        let history = history(&app);
+        assert_eq!(history.attempts.len(), 1);
+        assert!(history.attempts[0].completed);
+        assert_eq!(history.attempts[0].splits.len(), 3);
+        assert_eq!(history.attempts[0].splits[2].real_time, secs(2));
+        assert_eq!(app.selected_split, 0);
+        assert!(app.chrono.is_stopped());
+        // Fewer hits than the PB make the run the new PB.
+        assert_eq!(splits(&app)[0].pb, 1);
+        assert_eq!(splits(&app)[0].pb_time, Some(secs(2)));
+    }
+
+    #[test]
+    fn next_split_on_last_split_without_reset() {
+        let (mut app, _) = app();
+        app.config.next_split_as_reset = false;
+        app.selected_split = 2;
+        next_split(&mut app).unwrap();
+        assert_eq!(app.selected_split, 2);
+        assert!(history(&app).attempts.is_empty());
+    }
+
+    #[test]
+    fn reset_records_attempt() {
+        let (mut app, clock) = app();
+        start_chrono(&mut app).unwrap();
+        advance(&mut app, &clock, secs(3));
+        add_hit(&mut app).unwrap();
+        next_split(&mut app).unwrap();
+        advance(&mut app, &clock, secs(1));
+        reset(&mut app).unwrap();
+
+        let history = history(&app);
+        assert_eq!(history.attempts.len(), 1);
+        let attempt = &history.attempts[0];
+        assert!(!attempt.completed);
+        assert!(attempt.started_at.is_some());
+        assert_eq!(attempt.splits.len(), 2);
+        assert_eq!(attempt.splits[0].hits, 1);
+        assert_eq!(attempt.splits[0].real_time, secs(3));
+
+        assert_eq!(app.selected_split, 0);
+        assert!(app.chrono.is_stopped());
+        assert!(app.attempt_started.is_none());
+        assert!(splits(&app)
+            .iter()
+            .all(|s| s.hits == 0 && s.real_time.is_zero()));
+    }
+
+    #[test]
+    fn reset_without_progress_records_nothing() {
+        let (mut app, _) = app();
+        reset(&mut app).unwrap();
+        assert!(history(&app).attempts.is_empty());
+    }
+
+    #[test]
+    fn sets_pb() {
+        let (mut app, clock) = app();
+        start_chrono(&mut app).unwrap();
+        add_hit(&mut app).unwrap();
+        advance(&mut app, &clock, secs(6));
+        next_split(&mut app).unwrap();
+        set_pb(&mut app).unwrap();
+        let splits = splits(&app);
+        assert_eq!(splits[0].pb, 1);
+        assert_eq!(splits[0].pb_time, Some(secs(6)));
+        assert_eq!(splits[1].pb, 0);
+        assert_eq!(splits[1].pb_time, None);
+    }
+
+    #[test]
+    fn starts_and_pauses() {
+        let (mut app, clock) = app();
+        app.loaded_category.as_mut().unwrap().start_offset = SignedDuration::from_secs_f64(-2.0);
+        start_chrono(&mut app).unwrap();
+        assert!(app.attempt_started.is_some());
+        advance(&mut app, &clock, secs(1));
+        assert_eq!(splits(&app)[0].real_time, Duration::default());
+        advance(&mut app, &clock, secs(3));
+        assert_eq!(splits(&app)[0].real_time, secs(2));
+
+        pause_chrono(&mut app).unwrap();
+        advance(&mut app, &clock, secs(5));
+        assert_eq!(splits(&app)[0].real_time, secs(2));
+        assert_eq!(splits(&app)[0].paused_time, secs(5));
+
+        let started = app.attempt_started;
+        start_chrono(&mut app).unwrap();
+        advance(&mut app, &clock, secs(1));
+        assert_eq!(splits(&app)[0].real_time, secs(3));
+        assert_eq!(app.attempt_started, started);
+    }
+}
@@ -1,5 +1,8 @@
+pub mod assets;
 pub mod category;
 pub mod chrono;
 pub mod game;
+pub mod history;
+pub mod integrity;
 pub mod manager;
 pub mod split;
@@ -8,8 +8,19 @@ pub struct Split {
     pub icon_path: Option<PathBuf>,
     pub name: String,
     pub hits: u16,
+    /// Hits taken on the way to the split, as counted by HitCounterManager.
+    #[serde(default)]
+    pub way_hits: u16,
+    /// Hits of this split in the personal best run, way hits included.
     pub pb: u16,
     pub real_time: Duration,
+    /// Time the chronometer was paused during this split.
+    #[serde(default)]
+    pub paused_time: Duration,
+    /// Time of this split in the personal best run.
+    pub pb_time: Option<Duration>,
+    /// Best time ever achieved on this split, as imported from other tools.
+    pub gold_time: Option<Duration>,
 }
 
 impl Split {
@@ -20,6 +31,10 @@ impl Split {
         }
     }
 
+    pub fn total_hits(&self) -> u16 {
+        self.hits.saturating_add(self.way_hits)
+    }
+
     pub fn clear_icon_path(&mut self) {
         self.icon_path = None;
     }
@@ -0,0 +1,182 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Upgrades the json of a stored file by one version.
+pub type Migration = fn(&mut Value);
+
+/// Data stored as a json file. Every file carries the version of its schema in a
+/// top-level `version` key, and older files are upgraded on load. Files written before
+/// versioning have no key and are version 0.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// `MIGRATIONS[n]` upgrades a file from version `n` to `n + 1`, so the current version
+    /// is the number of migrations. Fields added with a default value need no migration.
+    const MIGRATIONS: &'static [Migration];
+
+    fn version() -> u64 {
+        Self::MIGRATIONS.len() as u64
+    }
+}
+
+/// Serializes `value` with the current version of its schema.
+pub fn to_string<T: Versioned>(value: &T) -> Result<String, serde_json::Error> {
+    let mut json = serde_json::to_value(value)?;
+    if let Value::Object(map) = &mut json {
+        map.insert("version".to_owned(), T::version().into());
+    }
+    serde_json::to_string(&json)
+}
+
+/// Parses a stored file, running the migrations from its version to the current one.
+pub fn from_str<T: Versioned>(json: &str) -> Result<T, serde_json::Error> {
+    let mut json: Value = serde_json::from_str(json)?;
+    let version = json.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > T::version() {
+        return Err(serde::de::Error::custom(format!(
+            "file version {version} is newer than the supported version {}, update HitSplit",
+            T::version()
+        )));
+    }
+    T::MIGRATIONS
+        .iter()
+        .skip(version as usize)
+        .for_each(|migration| migration(&mut json));
+    serde_json::from_value(json)
+}
+
+/// Migration of files whose content did not change when versioning was introduced.
+pub fn unversioned(_: &mut Value) {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use global_hotkey::hotkey::Code;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        run::{category::Category, game::Game},
+        settings::{
+            config::Config,
+            shortcut::{KeyBinding, Shortcut, ShortcutAction},
+        },
+    };
+
+    /// Files as saved by HitSplit 0.4, before versioning.
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!("../tests/fixtures/v0.4/", $name))
+        };
+    }
+
+    #[test]
+    fn loads_unversioned_config() {
+        let config: Config = from_str(fixture!("config.json")).unwrap();
+        assert_eq!(config.num_splits_counter, 7);
+        assert_eq!(config.chrono_format.template(), "{mm}:{ss}.{ff}");
+        assert_eq!(config.game_list.len(), 1);
+        assert_eq!(config.game_list[0].name, "Dark Souls");
+        assert_eq!(config.counter_size, [280.0, 600.0].into());
+        assert!(config.column_formats.is_empty());
+    }
+
+    #[test]
+    fn fills_legacy_config_defaults() {
+        let config: Config = from_str(fixture!("config_minimal.json")).unwrap();
+        assert_eq!(config.num_splits_counter, 10);
+        assert!(!config.dark_mode);
+        assert!(!config.autosave);
+        assert_eq!(
+            config.autosave_interval,
+            Config::default().autosave_interval
+        );
+
+        // Only unversioned files get the legacy default.
+        let json = json!({ "version": Config::version(), "game_list": [] }).to_string();
+        let config: Config = from_str(&json).unwrap();
+        assert_eq!(
+            config.num_splits_counter,
+            Config::default().num_splits_counter
+        );
+    }
+
+    fn assert_default_bindings(shortcut: &Shortcut) {
+        let defaults = Shortcut::default();
+        for action in ShortcutAction::ALL {
+            assert!(shortcut.bindings(action) == defaults.bindings(action));
+        }
+    }
+
+    #[test]
+    fn loads_unversioned_shortcuts() {
+        let shortcut: Shortcut = from_str(fixture!("shortcuts.json")).unwrap();
+        assert_default_bindings(&shortcut);
+        assert!(shortcut.bindings(ShortcutAction::LockHotkeys).is_empty());
+    }
+
+    #[test]
+    fn migrates_each_shortcut_version() {
+        let v1 = json!({ "version": 1, "keys": ["Numpad8", "Numpad2", "Numpad7", "Numpad9",
+            "Numpad5", "Numpad3", "Numpad4", "Numpad6"] });
+        assert_default_bindings(&from_str(&v1.to_string()).unwrap());
+
+        let v2 =
+            json!({ "version": 2, "keys": [{ "code": "F1", "ctrl": true }, { "code": "F2" }] });
+        let shortcut: Shortcut = from_str(&v2.to_string()).unwrap();
+        let mut prev = KeyBinding::new(Code::F1);
+        prev.ctrl = true;
+        assert!(shortcut.bindings(ShortcutAction::PrevSplit) == [prev]);
+        assert!(shortcut.bindings(ShortcutAction::NextSplit) == [KeyBinding::new(Code::F2)]);
+        assert!(shortcut.bindings(ShortcutAction::AddHit).is_empty());
+    }
+
+    #[test]
+    fn loads_unversioned_category() {
+        let category: Category = from_str(fixture!("category.json")).unwrap();
+        assert_eq!(category.splits.len(), 2);
+        let [kept, filled] = &category.splits[..] else {
+            unreachable!()
+        };
+        assert_eq!(
+            kept.uuid.as_deref(),
+            Some("0c1d2e3f-4a5b-4c6d-8e7f-9a0b1c2d3e4f")
+        );
+        assert_eq!(kept.real_time, Duration::from_millis(95250));
+        assert_eq!(kept.hits, 2);
+        assert!(filled
+            .uuid
+            .as_deref()
+            .is_some_and(|uuid| uuid::Uuid::parse_str(uuid).is_ok()));
+        assert_eq!(filled.pb, 3);
+        assert!(filled.icon_path.is_some());
+        assert!(category.start_offset == Default::default());
+    }
+
+    #[test]
+    fn loads_unversioned_game() {
+        let game: Game = from_str(fixture!("game.json")).unwrap();
+        assert_eq!(game.name, "Dark Souls");
+        assert_eq!(game.categories.len(), 1);
+        assert_eq!(game.categories[0].name, "Any%");
+        assert!(game.shortcut_overrides.is_none());
+    }
+
+    #[test]
+    fn saves_current_version() {
+        let game: Game = from_str(fixture!("game.json")).unwrap();
+        let json: Value = serde_json::from_str(&to_string(&game).unwrap()).unwrap();
+        assert_eq!(json["version"], Game::version());
+        let game: Game = from_str(&json.to_string()).unwrap();
+        assert_eq!(game.categories.len(), 1);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let json = json!({ "version": Category::version() + 1, "uuid": "a", "name": "b",
+            "icon_path": null, "splits": [] });
+        let e = from_str::<Category>(&json.to_string()).err().unwrap();
+        assert!(e.to_string().contains("newer"), "{e}");
+        let json = json!({ "version": Shortcut::version() + 1, "bindings": {} });
+        assert!(from_str::<Shortcut>(&json.to_string()).is_err());
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    logging,
+    run::{
+        chrono::{duration_chrono_format, ChronometerFormat},
+        manager::{
+            add_hit, next_split, pause_chrono, prev_split, reset, set_pb, start_chrono, sub_hit,
+        },
+        split::Split,
+    },
+    Error, HitSplit,
+};
+
+/// Default port of the LiveSplit Server component.
+pub const DEFAULT_PORT: u16 = 16834;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A command received from a client, answered through `reply` once it has been run.
+struct ServerCommand {
+    name: String,
+    args: String,
+    reply: Sender<Option<String>>,
+}
+
+/// Local TCP listener that understands the LiveSplit Server protocol. Commands are
+/// queued and run on the UI thread by `server_handler`.
+pub struct Server {
+    pub port: u16,
+    stop: Arc<AtomicBool>,
+    receiver: Receiver<ServerCommand>,
+}
+
+impl Server {
+    pub fn start(port: u16, ctx: egui::Context) -> Result<Self, Error> {
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+            Ok(l) => l,
+            Err(e) => {
+                return Err(Error::io(
+                    format!("Could not start the server on port {port}"),
+                    e.to_string(),
+                ))
+            }
+        };
+        if let Err(e) = listener.set_nonblocking(true) {
+            return Err(Error::io(
+                format!("Could not start the server on port {port}"),
+                e.to_string(),
+            ));
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = channel();
+        let thread_stop = stop.clone();
+        thread::spawn(move || listen(listener, sender, thread_stop, ctx));
+        logging::info(&format!("Server listening on port {port}"));
+
+        Ok(Self {
+            port,
+            stop,
+            receiver,
+        })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn listen(
+    listener: TcpListener,
+    sender: Sender<ServerCommand>,
+    stop: Arc<AtomicBool>,
+    ctx: egui::Context,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let sender = sender.clone();
+                let stop = stop.clone();
+                let ctx = ctx.clone();
+                thread::spawn(move || handle_client(stream, sender, stop, ctx));
+            }
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+fn handle_client(
+    stream: TcpStream,
+    sender: Sender<ServerCommand>,
+    stop: Arc<AtomicBool>,
+    ctx: egui::Context,
+) {
+    if stream.set_nonblocking(false).is_err()
+        || stream.set_read_timeout(Some(POLL_INTERVAL)).is_err()
+    {
+        return;
+    }
+    let mut writer = match stream.try_clone() {
+        Ok(w) => w,
+        Err(_) => return,
+    };
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        match reader.read_line(&mut line) {
+            Ok(0) => return,
+            Ok(_) => (),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(_) => return,
+        }
+
+        let text = std::mem::take(&mut line);
+        let (name, args) = match text.trim().split_once(' ') {
+            Some((name, args)) => (name.to_lowercase(), args.trim().to_owned()),
+            None => (text.trim().to_lowercase(), String::new()),
+        };
+        if name.is_empty() {
+            continue;
+        }
+
+        let (reply, reply_receiver) = channel();
+        if sender.send(ServerCommand { name, args, reply }).is_err() {
+            return;
+        }
+        ctx.request_repaint();
+
+        if let Ok(Some(answer)) = reply_receiver.recv_timeout(REPLY_TIMEOUT) {
+            if writer
+                .write_all(format!("{answer}\r\n").as_bytes())
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Runs a command on the app. Queries return the answer to send back to the client.
+fn execute(app: &mut HitSplit, name: &str, args: &str) -> Result<Option<String>, Error> {
+    match name {
+        "starttimer" => {
+            if app.chrono.is_stopped() {
+                start_chrono(app)?;
+            }
+        }
+        "startorsplit" => {
+            if app.chrono.is_stopped() {
+                start_chrono(app)?;
+            } else {
+                next_split(app)?;
+            }
+        }
+        // Like LiveSplit, splits are ignored until the timer is started.
+        "split" | "skipsplit" => {
+            if !app.chrono.is_stopped() {
+                next_split(app)?;
+            }
+        }
+        "unsplit" => {
+            if !app.chrono.is_stopped() {
+                prev_split(app)?;
+            }
+        }
+        "reset" => reset(app)?,
+        "pause" => pause_chrono(app)?,
+        "resume" => {
+            // Starting a running chronometer would drop the time since it was started.
+            if app.chrono.is_paused() {
+                start_chrono(app)?;
+            }
+        }
+        "getcurrenttime" => {
+            // The chronometer restarts on every split, so the run time adds up the
+            // previous splits.
+            let previous: Duration = match &app.loaded_category {
+                Some(category) => category
+                    .splits
+                    .iter()
+                    .take(app.selected_split)
+                    .map(|split| split.real_time)
+                    .sum(),
+                None => Duration::default(),
+            };
+            let format = ChronometerFormat::parse("{h}:{mm}:{ss}.{ff}").unwrap_or_default();
+            let time = app.chrono.get_signed_time()?.add_duration(previous);
+            return Ok(Some(duration_chrono_format(time, &format)));
+        }
+        "getsplitindex" => {
+            return Ok(Some(if app.chrono.is_stopped() {
+                "-1".to_string()
+            } else {
+                app.selected_split.to_string()
+            }))
+        }
+        "getcurrentsplitname" => {
+            let name = app
+                .loaded_category
+                .as_ref()
+                .and_then(|c| c.splits.get(app.selected_split))
+                .map(|s| s.name.clone())
+                .unwrap_or_default();
+            return Ok(Some(name));
+        }
+        "getcurrenttimerphase" => return Ok(Some(app.chrono.phase().to_string())),
+        // HitSplit extensions
+        "addhit" => {
+            for _ in 0..args.parse::<u16>().unwrap_or(1) {
+                add_hit(app)?;
+            }
+        }
+        "subhit" => {
+            for _ in 0..args.parse::<u16>().unwrap_or(1) {
+                sub_hit(app)?;
+            }
+        }
+        "setpb" => set_pb(app)?,
+        "gethits" => {
+            let hits = app
+                .loaded_category
+                .as_ref()
+                .and_then(|c| c.splits.get(app.selected_split))
+                .map(Split::total_hits)
+                .unwrap_or_default();
+            return Ok(Some(hits.to_string()));
+        }
+        "gettotalhits" => {
+            let hits = match &app.loaded_category {
+                Some(category) => category.splits.iter().map(Split::total_hits).sum::<u16>(),
+                None => 0,
+            };
+            return Ok(Some(hits.to_string()));
+        }
+        "getpb" => {
+            let pb = match &app.loaded_category {
+                Some(category) => category.splits.iter().map(|split| split.pb).sum::<u16>(),
+                None => 0,
+            };
+            return Ok(Some(pb.to_string()));
+        }
+        _ => (),
+    }
+    Ok(None)
+}
+
+pub fn server_handler(app: &mut HitSplit) -> Result<(), Error> {
+    let commands: Vec<ServerCommand> = match &app.server {
+        Some(server) => server.receiver.try_iter().collect(),
+        None => return Ok(()),
+    };
+    let mut result = Ok(());
+    for command in commands {
+        match execute(app, &command.name, &command.args) {
+            Ok(answer) => {
+                let _ = command.reply.send(answer);
+            }
+            Err(e) => {
+                let _ = command.reply.send(None);
+                result = Err(e);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        run::{
+            category::Category, chrono::Chronometer, history::History, manager::store_split_time,
+        },
+        storage, ManualClock,
+    };
+
+    fn app() -> (HitSplit, ManualClock) {
+        storage::use_test_storage();
+        let clock = ManualClock::default();
+        let mut category = Category::new(uuid::Uuid::new_v4().to_string(), "Any%".to_string());
+        category.splits = vec![Split::new(None), Split::new(None)];
+        let app = HitSplit {
+            loaded_category: Some(category),
+            chrono: Chronometer::with_clock(Default::default(), Arc::new(clock.clone())),
+            ..Default::default()
+        };
+        (app, clock)
+    }
+
+    fn time(app: &mut HitSplit) -> Option<String> {
+        execute(app, "getcurrenttime", "").unwrap()
+    }
+
+    #[test]
+    fn current_time_covers_the_whole_run() {
+        let (mut app, clock) = app();
+        assert_eq!(time(&mut app).as_deref(), Some("0:00:00.00"));
+        execute(&mut app, "starttimer", "").unwrap();
+        clock.advance(Duration::from_secs(65));
+        store_split_time(&mut app).unwrap();
+        execute(&mut app, "split", "").unwrap();
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(time(&mut app).as_deref(), Some("0:01:06.50"));
+        assert_eq!(
+            execute(&mut app, "getsplitindex", "").unwrap().as_deref(),
+            Some("1")
+        );
+    }
+
+    #[test]
+    fn resume_only_affects_paused_timer() {
+        let (mut app, clock) = app();
+        execute(&mut app, "resume", "").unwrap();
+        assert!(app.chrono.is_stopped());
+
+        execute(&mut app, "starttimer", "").unwrap();
+        clock.advance(Duration::from_secs(5));
+        execute(&mut app, "resume", "").unwrap();
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(time(&mut app).as_deref(), Some("0:00:06.00"));
+
+        execute(&mut app, "pause", "").unwrap();
+        clock.advance(Duration::from_secs(10));
+        execute(&mut app, "resume", "").unwrap();
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(time(&mut app).as_deref(), Some("0:00:07.00"));
+    }
+
+    #[test]
+    fn splits_are_ignored_until_started() {
+        let (mut app, _) = app();
+        app.selected_split = 1;
+        execute(&mut app, "unsplit", "").unwrap();
+        assert_eq!(app.selected_split, 1);
+        execute(&mut app, "split", "").unwrap();
+        execute(&mut app, "skipsplit", "").unwrap();
+        assert_eq!(app.selected_split, 1);
+        assert!(app.chrono.is_stopped());
+        let uuid = app.loaded_category.as_ref().unwrap().uuid.clone();
+        assert!(History::load(uuid).unwrap().attempts.is_empty());
+
+        app.selected_split = 0;
+        execute(&mut app, "starttimer", "").unwrap();
+        execute(&mut app, "split", "").unwrap();
+        assert_eq!(app.selected_split, 1);
+        execute(&mut app, "unsplit", "").unwrap();
+        assert_eq!(app.selected_split, 0);
+    }
+
+    #[test]
+    fn hits_include_way_hits() {
+        let (mut app, _) = app();
+        let splits = &mut app.loaded_category.as_mut().unwrap().splits;
+        splits[0].way_hits = 2;
+        splits[1].way_hits = 1;
+        execute(&mut app, "addhit", "3").unwrap();
+        assert_eq!(
+            execute(&mut app, "gethits", "").unwrap().as_deref(),
+            Some("5")
+        );
+        assert_eq!(
+            execute(&mut app, "gettotalhits", "").unwrap().as_deref(),
+            Some("6")
+        );
+    }
+}
@@ -6,21 +6,24 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     run::{
-        chrono::{duration_chrono_format, ChronometerFormat},
+        assets,
+        chrono::{duration_chrono_format, SignedDuration},
         split::Split,
     },
     HitSplit,
 };
 
-#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Column {
     Icon,
     SplitName,
     Hits,
+    WayHits,
     Difference,
     PersonalBest,
     Chrono,
     ChronoAcum,
+    PausedTime,
 }
 
 impl Column {
@@ -29,10 +32,12 @@ impl Column {
             Column::Icon => 0,
             Column::SplitName => 1,
             Column::Hits => 2,
-            Column::Difference => 3,
-            Column::PersonalBest => 4,
-            Column::Chrono => 5,
-            Column::ChronoAcum => 6,
+            Column::WayHits => 3,
+            Column::Difference => 4,
+            Column::PersonalBest => 5,
+            Column::Chrono => 6,
+            Column::ChronoAcum => 7,
+            Column::PausedTime => 8,
         }
     }
 
@@ -54,6 +59,9 @@ impl Column {
             Column::Hits => tr.col(|ui| {
                 ui.colored_label(color, "Hits");
             }),
+            Column::WayHits => tr.col(|ui| {
+                ui.colored_label(color, "Way");
+            }),
             Column::Difference => tr.col(|ui| {
                 ui.colored_label(color, "Diff");
             }),
@@ -66,6 +74,9 @@ impl Column {
             Column::ChronoAcum => tr.col(|ui| {
                 ui.colored_label(color, "Chrono Ac.");
             }),
+            Column::PausedTime => tr.col(|ui| {
+                ui.colored_label(color, "Paused");
+            }),
         };
     }
 
@@ -75,19 +86,14 @@ impl Column {
         index: usize,
         split: &Split,
         label_color: Color32,
-        chrono_format: &ChronometerFormat,
         row: &mut TableRow,
     ) {
+        let chrono_format = app.config.column_format(self);
         match self {
             Column::Icon => {
                 row.col(|ui| {
                     if let Some(p) = &split.icon_path {
-                        if let Some(path) = p.as_path().to_str() {
-                            ui.add(
-                                egui::Image::new(format!("file://{path}"))
-                                    .max_height(app.config.font_size),
-                            );
-                        };
+                        ui.add(egui::Image::new(assets::uri(p)).max_height(app.config.font_size));
                     }
                 });
             }
@@ -106,11 +112,16 @@ impl Column {
                     ui.colored_label(label_color, split.hits.to_string());
                 });
             }
+            Column::WayHits => {
+                row.col(|ui| {
+                    ui.colored_label(label_color, split.way_hits.to_string());
+                });
+            }
             Column::Difference => {
                 row.col(|ui| {
                     ui.colored_label(
                         label_color,
-                        (i32::from(split.hits) - i32::from(split.pb)).to_string(),
+                        (i32::from(split.total_hits()) - i32::from(split.pb)).to_string(),
                     );
                 });
             }
@@ -123,7 +134,7 @@ impl Column {
                 row.col(|ui| {
                     ui.colored_label(
                         label_color,
-                        duration_chrono_format(split.real_time, chrono_format),
+                        duration_chrono_format(split_time(app, index, split), chrono_format),
                     );
                 });
             }
@@ -138,15 +149,27 @@ impl Column {
                             .sum();
                         ui.colored_label(
                             label_color,
-                            duration_chrono_format(acum + split.real_time, chrono_format),
+                            duration_chrono_format(
+                                split_time(app, index, split).add_duration(acum),
+                                chrono_format,
+                            ),
                         );
                     }
                 });
             }
+            Column::PausedTime => {
+                row.col(|ui| {
+                    ui.colored_label(
+                        label_color,
+                        duration_chrono_format(split.paused_time, chrono_format),
+                    );
+                });
+            }
         }
     }
 
     pub fn total(&self, app: &HitSplit, label_color: Color32, row: &mut TableRow) {
+        let chrono_format = app.config.column_format(self);
         match self {
             Column::Icon => row.col(|_| {}),
             Column::SplitName => row.col(|ui| {
@@ -160,12 +183,23 @@ impl Column {
 
                 ui.colored_label(label_color, hits.to_string());
             }),
+            Column::WayHits => row.col(|ui| {
+                let hits = match &app.loaded_category {
+                    Some(category) => category
+                        .splits
+                        .iter()
+                        .map(|split| split.way_hits)
+                        .sum::<u16>(),
+                    None => 0,
+                };
+                ui.colored_label(label_color, hits.to_string());
+            }),
             Column::Difference => row.col(|ui| {
                 let diff = match &app.loaded_category {
                     Some(category) => category
                         .splits
                         .iter()
-                        .map(|split| i32::from(split.hits) - i32::from(split.pb))
+                        .map(|split| i32::from(split.total_hits()) - i32::from(split.pb))
                         .sum::<i32>(),
                     None => 0,
                 };
@@ -181,14 +215,21 @@ impl Column {
             Column::Chrono => row.col(|ui| {
                 if let Some(category) = &app.loaded_category {
                     let acum: Duration = category.splits.iter().map(|s| s.real_time).sum();
-                    let time: String = duration_chrono_format(acum, &app.config.chrono_format);
+                    let time: String = duration_chrono_format(acum, chrono_format);
                     ui.colored_label(label_color, time);
                 }
             }),
             Column::ChronoAcum => row.col(|ui| {
                 if let Some(category) = &app.loaded_category {
                     let acum: Duration = category.splits.iter().map(|s| s.real_time).sum();
-                    let time: String = duration_chrono_format(acum, &app.config.chrono_format);
+                    let time: String = duration_chrono_format(acum, chrono_format);
+                    ui.colored_label(label_color, time);
+                }
+            }),
+            Column::PausedTime => row.col(|ui| {
+                if let Some(category) = &app.loaded_category {
+                    let paused: Duration = category.splits.iter().map(|s| s.paused_time).sum();
+                    let time: String = duration_chrono_format(paused, chrono_format);
                     ui.colored_label(label_color, time);
                 }
             }),
@@ -196,6 +237,17 @@ impl Column {
     }
 }
 
+/// The selected split shows the chronometer time, which may be negative
+/// before the start offset has elapsed.
+fn split_time(app: &HitSplit, index: usize, split: &Split) -> SignedDuration {
+    if index == app.selected_split {
+        if let Ok(time) = app.chrono.get_signed_time() {
+            return time;
+        }
+    }
+    split.real_time.into()
+}
+
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ColumnVec(Vec<Column>);
 
@@ -1,71 +1,23 @@
-use std::fs::read_dir;
+use std::collections::HashMap;
 
 use egui::Vec2;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::{
-    get_config_path,
+    logging::Level,
     run::{chrono::ChronometerFormat, game::SmallGame},
+    schema::{self, Migration, Versioned},
+    server::DEFAULT_PORT,
+    storage::{self, Record},
     Error,
 };
 
-use super::columns::ColumnVec;
+use super::columns::{Column, ColumnVec};
 
-#[derive(Deserialize)]
-pub struct OptionalConfig {
-    dark_mode: Option<bool>,
-    next_split_as_reset: Option<bool>,
-    autosave: Option<bool>,
-    autosave_interval: Option<u64>,
-    game_list: Option<Vec<SmallGame>>,
-    font_size: Option<f32>,
-    limit_splits_shown: Option<bool>,
-    num_splits_counter: Option<usize>,
-    counter_size: Option<Vec2>,
-    columns: Option<ColumnVec>,
-    chrono_format: Option<ChronometerFormat>,
-    game_image_height: Option<f32>,
-    category_image_height: Option<f32>,
-    background_color: Option<[u8; 3]>,
-    background_transparency: Option<u8>,
-    text_color_default: Option<[u8; 3]>,
-    text_color_nohit: Option<[u8; 3]>,
-    text_color_better: Option<[u8; 3]>,
-    text_color_worse: Option<[u8; 3]>,
-    always_on_top: Option<bool>,
-}
-
-impl OptionalConfig {
-    fn to_config(&self) -> Config {
-        Config {
-            dark_mode: self.dark_mode.unwrap_or(true),
-            next_split_as_reset: self.next_split_as_reset.unwrap_or(true),
-            autosave: self.autosave.unwrap_or(true),
-            autosave_interval: self.autosave_interval.unwrap_or(60),
-            game_list: match &self.game_list {
-                None => Vec::new(),
-                Some(v) => v.to_vec(),
-            },
-            font_size: self.font_size.unwrap_or(14.0),
-            limit_splits_shown: self.limit_splits_shown.unwrap_or(false),
-            num_splits_counter: self.num_splits_counter.unwrap_or(10),
-            counter_size: self.counter_size.unwrap_or([280.0, 600.0].into()),
-            columns: self.columns.clone().unwrap_or_default(),
-            chrono_format: self.chrono_format.unwrap_or(ChronometerFormat::HHMMSSX),
-            game_image_height: self.game_image_height.unwrap_or(46.),
-            category_image_height: self.category_image_height.unwrap_or(40.),
-            background_color: self.background_color.unwrap_or([28, 28, 28]),
-            background_transparency: self.background_transparency.unwrap_or(255),
-            text_color_default: self.text_color_default.unwrap_or([240, 240, 240]),
-            text_color_nohit: self.text_color_nohit.unwrap_or([8, 250, 8]),
-            text_color_better: self.text_color_better.unwrap_or([250, 250, 8]),
-            text_color_worse: self.text_color_worse.unwrap_or([250, 8, 8]),
-            always_on_top: self.always_on_top.unwrap_or(true),
-        }
-    }
-}
-
-#[derive(Serialize, Clone)]
+/// Fields missing from the file take their value from `Config::default`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct Config {
     pub dark_mode: bool,
     pub next_split_as_reset: bool,
@@ -78,6 +30,8 @@ pub struct Config {
     pub counter_size: Vec2,
     pub columns: ColumnVec,
     pub chrono_format: ChronometerFormat,
+    /// Overrides `chrono_format` for specific columns.
+    pub column_formats: HashMap<Column, ChronometerFormat>,
     pub game_image_height: f32,
     pub category_image_height: f32,
     pub background_color: [u8; 3],
@@ -87,6 +41,13 @@ pub struct Config {
     pub text_color_better: [u8; 3],
     pub text_color_worse: [u8; 3],
     pub always_on_top: bool,
+    /// Name written as the runner in exported runs.
+    pub runner_name: String,
+    /// Accept LiveSplit Server commands on a local TCP port.
+    pub tcp_server: bool,
+    pub tcp_server_port: u16,
+    /// Least severe level written to the log.
+    pub log_level: Level,
 }
 
 impl Default for Config {
@@ -102,7 +63,8 @@ impl Default for Config {
             num_splits_counter: 0,
             counter_size: [280.0, 600.0].into(),
             columns: ColumnVec::default(),
-            chrono_format: ChronometerFormat::HHMMSSX,
+            chrono_format: ChronometerFormat::default(),
+            column_formats: HashMap::new(),
             game_image_height: 46.,
             category_image_height: 40.,
             background_color: [28, 28, 28],
@@ -112,24 +74,25 @@ impl Default for Config {
             text_color_better: [250, 250, 8],
             text_color_worse: [250, 8, 8],
             always_on_top: true,
+            runner_name: String::new(),
+            tcp_server: false,
+            tcp_server_port: DEFAULT_PORT,
+            log_level: Level::Info,
         }
     }
 }
 
 impl Config {
+    pub fn column_format(&self, column: &Column) -> &ChronometerFormat {
+        self.column_formats
+            .get(column)
+            .unwrap_or(&self.chrono_format)
+    }
+
     pub fn save(&mut self) -> Result<(), Error> {
-        let config_path: String = get_config_path();
-        match serde_json::to_string(self) {
-            Ok(config_str) => {
-                match std::fs::write(format!("{config_path}/config.json"), config_str) {
-                    Ok(_) => Ok(()),
-                    Err(e) => Err(Error::new(
-                        format!("Error saving file \"config.json\" at location \"{config_path}\""),
-                        e.to_string(),
-                    )),
-                }
-            }
-            Err(e) => Err(Error::new(
+        match schema::to_string(self) {
+            Ok(config_str) => storage::write(&Record::Config, &config_str),
+            Err(e) => Err(Error::parse(
                 "Could not convert Config into String".to_string(),
                 e.to_string(),
             )),
@@ -137,80 +100,43 @@ impl Config {
     }
 
     pub fn load() -> Result<Self, Error> {
-        let config_path = get_config_path();
-
-        if read_dir(&config_path).is_err() {
-            match std::fs::create_dir(&config_path) {
-                Ok(_) => (),
-                Err(e) => {
-                    return Err(Error::new(
-                        format!("Could not create config directory: \"{config_path}\""),
-                        e.to_string(),
-                    ))
-                }
-            };
-        }
-
-        if read_dir(format!("{config_path}/games")).is_err() {
-            match std::fs::create_dir(format!("{config_path}/games")) {
-                Ok(_) => (),
-                Err(e) => {
-                    return Err(Error::new(
-                        format!("Could not create games directory: \"{config_path}/games\""),
-                        e.to_string(),
-                    ))
-                }
-            };
-        }
-
-        if read_dir(format!("{config_path}/categories")).is_err() {
-            match std::fs::create_dir(format!("{config_path}/categories")) {
-                Ok(_) => (),
-                Err(e) => {
-                    return Err(Error::new(
-                        format!(
-                            "Could not create categories directory: \"{config_path}/categories\""
-                        ),
-                        e.to_string(),
-                    ))
-                }
-            };
-        }
-
-        let config_json: String =
-            match std::fs::read_to_string(format!("{config_path}/config.json")) {
-                Err(_) => {
-                    let tmp: Config = Default::default();
-                    let config_str = match serde_json::to_string(&tmp) {
+        let config_json: String = match storage::read(&Record::Config)? {
+            None => {
+                let tmp: Config = Default::default();
+                let config_str = match schema::to_string(&tmp) {
                     Ok(cfg) => cfg,
-                    Err(e) => return Err(Error::new(
+                    Err(e) => return Err(Error::parse(
                         "Could not parse config default string. Please file an issue on github."
                             .to_string(),
                         e.to_string(),
                     )),
                 };
-                    match std::fs::write(format!("{config_path}/config.json"), config_str.clone()) {
-                        Ok(_) => (),
-                        Err(e) => {
-                            return Err(Error::new(
-                                format!(
-                                "Could not create file config.json: \"{config_path}/config.json\""
-                            ),
-                                e.to_string(),
-                            ))
-                        }
-                    };
-                    config_str
-                }
-                Ok(f) => f,
-            };
+                storage::write(&Record::Config, &config_str)?;
+                config_str
+            }
+            Some(f) => f,
+        };
 
-        match serde_json::from_str::<OptionalConfig>(config_json.as_str()) {
-            Ok(cfg) => Ok(cfg.to_config()),
-            Err(e) => Err(Error::new(
+        match schema::from_str(config_json.as_str()) {
+            Ok(cfg) => Ok(cfg),
+            Err(e) => Err(Error::parse(
                 "Could not load config from read json file".to_string(),
                 e.to_string(),
             )),
         }
     }
 }
+
+impl Versioned for Config {
+    const MIGRATIONS: &'static [Migration] = &[legacy_defaults];
+}
+
+/// Unversioned files were read with their own defaults, which only differ from
+/// `Config::default` in the number of splits shown.
+fn legacy_defaults(json: &mut Value) {
+    if let Some(config) = json.as_object_mut() {
+        config
+            .entry("num_splits_counter")
+            .or_insert_with(|| 10.into());
+    }
+}
@@ -3,6 +3,7 @@ use global_hotkey::hotkey::Code;
 
 pub mod columns;
 pub mod config;
+pub mod profile;
 pub mod shortcut;
 
 pub fn key_to_code(key: &Key) -> Code {
@@ -0,0 +1,118 @@
+use std::{fs::read_dir, sync::Mutex};
+
+use crate::{get_data_path, Error};
+
+/// Profile kept directly in the data directory, as all data was before profiles.
+pub const DEFAULT_PROFILE: &str = "Default";
+/// Environment variable choosing the profile at startup.
+pub const PROFILE_VAR: &str = "HITSPLIT_PROFILE";
+/// Folder of the data directory with one folder per profile other than the default one.
+const PROFILES_DIR: &str = "profiles";
+/// File of the data directory remembering the last profile chosen in the manager, which
+/// is used at startup when no other is given.
+const LAST_PROFILE_FILE: &str = "profile.txt";
+
+static PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Directory where the data of `profile` is kept.
+pub fn profile_path(profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        get_data_path()
+    } else {
+        format!("{}/{PROFILES_DIR}/{profile}", get_data_path())
+    }
+}
+
+fn exists(profile: &str) -> bool {
+    profile == DEFAULT_PROFILE || std::path::Path::new(&profile_path(profile)).is_dir()
+}
+
+/// Profile in use: the one set with `set_profile`, the one in `PROFILE_VAR`, the one
+/// remembered with `remember_profile`, or the default one.
+pub fn active_profile() -> String {
+    let Ok(mut profile) = PROFILE.lock() else {
+        return DEFAULT_PROFILE.to_owned();
+    };
+    profile
+        .get_or_insert_with(|| {
+            let last = std::fs::read_to_string(format!("{}/{LAST_PROFILE_FILE}", get_data_path()));
+            std::env::var(PROFILE_VAR)
+                .ok()
+                .into_iter()
+                .chain(last.ok().map(|l| l.trim().to_owned()))
+                .find(|p| exists(p))
+                .unwrap_or_else(|| DEFAULT_PROFILE.to_owned())
+        })
+        .clone()
+}
+
+/// Makes `profile` the one in use.
+pub fn set_profile(profile: &str) -> Result<(), Error> {
+    if !exists(profile) {
+        return Err(Error::missing(format!(
+            "Profile \"{profile}\" does not exist"
+        )));
+    }
+    if let Ok(mut active) = PROFILE.lock() {
+        *active = Some(profile.to_owned());
+    }
+    Ok(())
+}
+
+/// Remembers the profile in use, so it is chosen at the next start.
+pub fn remember_profile() -> Result<(), Error> {
+    let path = format!("{}/{LAST_PROFILE_FILE}", get_data_path());
+    match std::fs::create_dir_all(get_data_path())
+        .and_then(|_| std::fs::write(&path, active_profile()))
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(
+            format!("Could not save the last used profile to \"{path}\""),
+            e.to_string(),
+        )),
+    }
+}
+
+/// Names of the profiles, the default one first.
+pub fn profiles() -> Vec<String> {
+    let mut profiles: Vec<String> = read_dir(format!("{}/{PROFILES_DIR}", get_data_path()))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_owned());
+    profiles
+}
+
+/// Creates an empty profile. Names are used as folder names, so only letters, digits,
+/// spaces, `-` and `_` are allowed.
+pub fn create_profile(profile: &str) -> Result<(), Error> {
+    let valid = !profile.trim().is_empty()
+        && profile.trim() == profile
+        && profile
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_');
+    if !valid {
+        return Err(Error::invalid(format!(
+            "Invalid profile name \"{profile}\". Use letters, digits, spaces, - and _"
+        )));
+    }
+    if profiles().iter().any(|p| p.eq_ignore_ascii_case(profile)) {
+        return Err(Error::invalid(format!(
+            "Profile \"{profile}\" already exists"
+        )));
+    }
+    let path = profile_path(profile);
+    match std::fs::create_dir_all(&path) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(
+            format!("Could not create profile directory \"{path}\""),
+            e.to_string(),
+        )),
+    }
+}
@@ -1,23 +1,27 @@
-use std::fs::read_dir;
+use std::{collections::BTreeMap, fmt::Display};
 
 use egui::Key;
 use global_hotkey::{
-    hotkey::{Code, HotKey},
+    hotkey::{Code, HotKey, Modifiers},
     GlobalHotKeyEvent, HotKeyState,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 
 use crate::{
-    get_config_path,
+    logging,
     run::manager::{
         add_hit, next_split, pause_chrono, prev_split, reset, set_pb, start_chrono, sub_hit,
     },
+    schema::{self, Migration, Versioned},
+    storage::{self, Record},
     Error, HitSplit,
 };
 
 use super::key_to_code;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ShortcutAction {
     PrevSplit,
     NextSplit,
@@ -27,33 +31,34 @@ pub enum ShortcutAction {
     SetPb,
     StartChrono,
     PauseChrono,
+    /// Suspends every other action until it is pressed again.
+    LockHotkeys,
 }
 
 impl ShortcutAction {
-    pub fn from_usize(u: usize) -> Option<Self> {
-        match u {
-            0 => Some(ShortcutAction::PrevSplit),
-            1 => Some(ShortcutAction::NextSplit),
-            2 => Some(ShortcutAction::AddHit),
-            3 => Some(ShortcutAction::SubHit),
-            4 => Some(ShortcutAction::Reset),
-            5 => Some(ShortcutAction::SetPb),
-            6 => Some(ShortcutAction::StartChrono),
-            7 => Some(ShortcutAction::PauseChrono),
-            _ => None,
-        }
-    }
+    pub const ALL: [ShortcutAction; 9] = [
+        ShortcutAction::PrevSplit,
+        ShortcutAction::NextSplit,
+        ShortcutAction::AddHit,
+        ShortcutAction::SubHit,
+        ShortcutAction::Reset,
+        ShortcutAction::SetPb,
+        ShortcutAction::StartChrono,
+        ShortcutAction::PauseChrono,
+        ShortcutAction::LockHotkeys,
+    ];
 
-    pub fn to_usize(self) -> usize {
+    pub fn name(self) -> &'static str {
         match self {
-            ShortcutAction::PrevSplit => 0,
-            ShortcutAction::NextSplit => 1,
-            ShortcutAction::AddHit => 2,
-            ShortcutAction::SubHit => 3,
-            ShortcutAction::Reset => 4,
-            ShortcutAction::SetPb => 5,
-            ShortcutAction::StartChrono => 6,
-            ShortcutAction::PauseChrono => 7,
+            ShortcutAction::PrevSplit => "Previous split",
+            ShortcutAction::NextSplit => "Next split",
+            ShortcutAction::AddHit => "Add hit",
+            ShortcutAction::SubHit => "Substract hit",
+            ShortcutAction::Reset => "Reset",
+            ShortcutAction::SetPb => "Set current run as PB",
+            ShortcutAction::StartChrono => "Start chrono",
+            ShortcutAction::PauseChrono => "Pause chrono",
+            ShortcutAction::LockHotkeys => "Lock hotkeys",
         }
     }
 
@@ -67,142 +72,370 @@ impl ShortcutAction {
             ShortcutAction::SetPb => set_pb,
             ShortcutAction::StartChrono => start_chrono,
             ShortcutAction::PauseChrono => pause_chrono,
+            ShortcutAction::LockHotkeys => toggle_lock,
+        }
+    }
+
+    /// Binds the captured binding, unless the loaded game or category already uses it for
+    /// another action.
+    pub fn change_shortcut(app: &mut HitSplit, capture: Capture, binding: KeyBinding) {
+        let in_use = app
+            .active_shortcut()
+            .and_then(|s| s.action(binding.id()))
+            .is_some_and(|action| action != capture.action);
+        if in_use {
+            return;
+        }
+        if let Some(shortcut) = app.shortcut_mut(capture.target) {
+            shortcut.bind(capture.action, capture.index, binding);
+        }
+    }
+}
+
+/// Bindings that are edited: the global ones or the overrides of the loaded game or
+/// category.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ShortcutTarget {
+    Global,
+    Game,
+    Category,
+}
+
+/// Binding being captured in the settings page or the overrides window. It replaces the
+/// binding of `action` in `target` at `index`, or is added to them when there is no index.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct Capture {
+    pub target: ShortcutTarget,
+    pub action: ShortcutAction,
+    pub index: Option<usize>,
+}
+
+/// A key and the modifiers that must be held with it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: Code,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub shift: bool,
+    /// The Windows or Command key.
+    #[serde(default, rename = "super")]
+    pub super_key: bool,
+}
+
+impl KeyBinding {
+    pub fn new(code: Code) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            alt: false,
+            shift: false,
+            super_key: false,
+        }
+    }
+
+    /// Binding of a key pressed in the window. egui only reports the Super key as the
+    /// Command key on macOS.
+    pub fn from_key(key: &Key, modifiers: &egui::Modifiers) -> Self {
+        Self {
+            code: key_to_code(key),
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            super_key: modifiers.mac_cmd,
         }
     }
 
-    pub fn change_shortcut(app: &mut HitSplit, action: &ShortcutAction, key: &Key) {
-        if let Some(shortcut) = app.shortcut.as_mut() {
-            if !shortcut.0.contains(&key_to_code(key)) {
-                if let Some(sc) = shortcut.0.get_mut(action.to_usize()) {
-                    *sc = key_to_code(key);
-                }
+    pub fn to_hotkey(self) -> HotKey {
+        let mut modifiers = Modifiers::empty();
+        modifiers.set(Modifiers::CONTROL, self.ctrl);
+        modifiers.set(Modifiers::ALT, self.alt);
+        modifiers.set(Modifiers::SHIFT, self.shift);
+        modifiers.set(Modifiers::SUPER, self.super_key);
+        HotKey::new(Some(modifiers), self.code)
+    }
+
+    fn id(self) -> u32 {
+        self.to_hotkey().id()
+    }
+}
+
+impl Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+            (self.super_key, "Super"),
+        ] {
+            if held {
+                write!(f, "{name}+")?;
             }
         }
+        write!(f, "{}", self.code)
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Shortcut(pub Vec<Code>);
+/// Key bindings of each action. Actions without bindings are unbound.
+///
+/// Games and categories can hold a `Shortcut` of overrides, which only has entries for the
+/// actions it overrides.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Shortcut {
+    pub bindings: BTreeMap<ShortcutAction, Vec<KeyBinding>>,
+}
 
 impl Default for Shortcut {
     fn default() -> Self {
-        Shortcut(vec![
-            Code::Numpad8,
-            Code::Numpad2,
-            Code::Numpad7,
-            Code::Numpad9,
-            Code::Numpad5,
-            Code::Numpad3,
-            Code::Numpad4,
-            Code::Numpad6,
-        ])
+        Shortcut {
+            bindings: [
+                (ShortcutAction::PrevSplit, Code::Numpad8),
+                (ShortcutAction::NextSplit, Code::Numpad2),
+                (ShortcutAction::AddHit, Code::Numpad7),
+                (ShortcutAction::SubHit, Code::Numpad9),
+                (ShortcutAction::Reset, Code::Numpad5),
+                (ShortcutAction::SetPb, Code::Numpad3),
+                (ShortcutAction::StartChrono, Code::Numpad4),
+                (ShortcutAction::PauseChrono, Code::Numpad6),
+            ]
+            .into_iter()
+            .map(|(action, code)| (action, vec![KeyBinding::new(code)]))
+            .collect(),
+        }
     }
 }
 
 impl Shortcut {
+    /// Shortcut without bindings, used to start the overrides of a game or category.
+    pub fn empty() -> Self {
+        Shortcut {
+            bindings: BTreeMap::new(),
+        }
+    }
+
     pub fn save(&self) -> Result<(), Error> {
-        let config_path = get_config_path();
-        let shortcuts_str = match serde_json::to_string(self) {
+        let shortcuts_str = match schema::to_string(self) {
             Ok(sc) => sc,
             Err(e) => {
-                return Err(Error::new(
-                    format!("Could not save shortcuts in path \"{config_path}/shortcuts.json\""),
+                return Err(Error::parse(
+                    format!("Could not save shortcuts in {}", Record::Shortcuts),
                     e.to_string(),
                 ))
             }
         };
-        match std::fs::write(format!("{config_path}/shortcuts.json"), shortcuts_str) {
-            Ok(_) => (),
-            Err(e) => {
-                return Err(Error::new(
-                    format!("Error saving file \"shortcuts.json\" at location \"{config_path}\""),
-                    e.to_string(),
-                ))
-            }
-        };
-        Ok(())
+        storage::write(&Record::Shortcuts, &shortcuts_str)
     }
 
     pub fn load() -> Result<Self, Error> {
-        let config_path = get_config_path();
-        if read_dir(&config_path).is_err() {
-            let _ = std::fs::create_dir(&config_path);
-        }
-
-        let shortcuts_json: String =
-            match std::fs::read_to_string(format!("{config_path}/shortcuts.json")) {
-                Err(_) => {
-                    let tmp: Shortcut = Default::default();
-                    let shortcuts_str = match serde_json::to_string(&tmp) {
-                        Ok(cfg) => cfg,
-                        Err(e) => return Err(Error::new(
-                            "Could not parse config default string. Please file an issue on github."
-                                .to_string(),
-                            e.to_string(),
-                        )),
-                    };
-                    match std::fs::write(
-                    format!("{config_path}/shortcuts.json"),
-                    shortcuts_str.clone(),
-                ) {
-                    Ok(_) => shortcuts_str,
-                    Err(e) => return Err(Error::new(
+        let shortcuts_json: String = match storage::read(&Record::Shortcuts)? {
+            None => {
+                let tmp: Shortcut = Default::default();
+                let shortcuts_str = match schema::to_string(&tmp) {
+                    Ok(cfg) => cfg,
+                    Err(e) => return Err(Error::parse(
                         "Could not parse shortcut default string. Please file an issue on github."
                             .to_string(),
                         e.to_string(),
                     )),
-                }
-                }
-                Ok(f) => f,
-            };
+                };
+                storage::write(&Record::Shortcuts, &shortcuts_str)?;
+                shortcuts_str
+            }
+            Some(f) => f,
+        };
 
-        let shortcuts = match serde_json::from_str::<Shortcut>(shortcuts_json.as_str()) {
+        let shortcuts = match schema::from_str::<Shortcut>(shortcuts_json.as_str()) {
             Ok(sc) => sc,
             Err(e) => {
-                return Err(Error::new(
+                return Err(Error::parse(
                     "Could not parse json as shortcuts.".to_string(),
                     e.to_string(),
                 ))
             }
         };
-        if shortcuts.0.len() < 8 {
-            return Ok(Shortcut::default());
-        }
         Ok(shortcuts)
     }
 
-    pub fn code_to_hotkey(code: Code) -> HotKey {
-        HotKey::new(None, code)
+    pub fn bindings(&self, action: ShortcutAction) -> &[KeyBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
     }
 
-    fn code_to_id(code: Code) -> u32 {
-        Self::code_to_hotkey(code).id()
+    pub fn all_bindings(&self) -> impl Iterator<Item = &KeyBinding> {
+        self.bindings.values().flatten()
     }
+
+    /// Action bound to the hotkey with `id`.
+    pub fn action(&self, id: u32) -> Option<ShortcutAction> {
+        self.bindings
+            .iter()
+            .find(|(_, bindings)| bindings.iter().any(|b| b.id() == id))
+            .map(|(&action, _)| action)
+    }
+
+    /// Binds `binding` to `action`, replacing the binding at `index` or adding it. A
+    /// binding already in use is not bound again.
+    pub fn bind(&mut self, action: ShortcutAction, index: Option<usize>, binding: KeyBinding) {
+        if self.all_bindings().any(|&b| b == binding) {
+            return;
+        }
+        let bindings = self.bindings.entry(action).or_default();
+        match index.and_then(|i| bindings.get_mut(i)) {
+            Some(b) => *b = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    pub fn unbind(&mut self, action: ShortcutAction, index: usize) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            if index < bindings.len() {
+                bindings.remove(index);
+            }
+        }
+    }
+
+    /// Whether these bindings have an entry for `action`, even an empty one.
+    pub fn overrides(&self, action: ShortcutAction) -> bool {
+        self.bindings.contains_key(&action)
+    }
+
+    /// Removes the entry of `action`, so the bindings it overrode are used again.
+    pub fn inherit(&mut self, action: ShortcutAction) {
+        self.bindings.remove(&action);
+    }
+
+    /// These bindings with each of `overrides` applied in order. An overridden action takes
+    /// the bindings of the override, and is unbound when the override has none. A key bound
+    /// by an override is taken from every other action, so each key runs a single action.
+    pub fn with_overrides<'a>(&self, overrides: impl IntoIterator<Item = &'a Shortcut>) -> Self {
+        let mut shortcut = self.clone();
+        for o in overrides {
+            for (&action, bindings) in o.bindings.iter() {
+                shortcut
+                    .bindings
+                    .values_mut()
+                    .for_each(|bound| bound.retain(|b| !bindings.contains(b)));
+                shortcut.bindings.insert(action, bindings.clone());
+            }
+        }
+        shortcut
+    }
+}
+
+impl Versioned for Shortcut {
+    const MIGRATIONS: &'static [Migration] = &[keys_object, key_bindings, named_bindings];
+}
+
+/// Unversioned files hold the list of keys alone.
+fn keys_object(json: &mut Value) {
+    *json = json!({ "keys": json.take() });
 }
 
+/// Keys were bare codes, without modifiers.
+fn key_bindings(json: &mut Value) {
+    if let Some(keys) = json.get_mut("keys").and_then(Value::as_array_mut) {
+        keys.iter_mut()
+            .filter(|key| key.is_string())
+            .for_each(|key| *key = json!({ "code": key.take() }));
+    }
+}
+
+/// Keys were a list with one binding per action, in this order.
+fn named_bindings(json: &mut Value) {
+    const ACTIONS: [&str; 8] = [
+        "prev_split",
+        "next_split",
+        "add_hit",
+        "sub_hit",
+        "reset",
+        "set_pb",
+        "start_chrono",
+        "pause_chrono",
+    ];
+    let keys = match json.get_mut("keys").map(Value::take) {
+        Some(Value::Array(keys)) => keys,
+        _ => Vec::new(),
+    };
+    let bindings: Map<String, Value> = ACTIONS
+        .iter()
+        .zip(keys)
+        .map(|(action, key)| (action.to_string(), json!([key])))
+        .collect();
+    *json = json!({ "bindings": bindings });
+}
+
+fn toggle_lock(app: &mut HitSplit) -> Result<(), Error> {
+    app.hotkeys_locked = !app.hotkeys_locked;
+    logging::info(if app.hotkeys_locked {
+        "Hotkeys locked"
+    } else {
+        "Hotkeys unlocked"
+    });
+    Ok(())
+}
+
+/// Runs the action of a pressed hotkey. While hotkeys are locked, only the lock action runs.
 pub fn shortcut_handler(app: &mut HitSplit) -> Result<(), Error> {
     let receiver = GlobalHotKeyEvent::receiver();
     if let Ok(event) = receiver.try_recv() {
         if event.state == HotKeyState::Pressed {
-            if let Some(ref shortcut) = app.shortcut {
-                if let Some(index) = shortcut
-                    .0
-                    .iter()
-                    .enumerate()
-                    .find(|(_, &c)| event.id == Shortcut::code_to_id(c))
-                {
-                    match ShortcutAction::from_usize(index.0) {
-                        Some(sa) => return sa.to_function()(app),
-                        None => {
-                            return Err(Error::new(
-                                "Action not found. Please file an issue on github.".to_string(),
-                                "None".to_string(),
-                            ))
-                        }
-                    }
-                }
+            let action = app
+                .registered_shortcut
+                .as_ref()
+                .and_then(|s| s.action(event.id))
+                .filter(|&a| !app.hotkeys_locked || a == ShortcutAction::LockHotkeys);
+            if let Some(action) = action {
+                return action.to_function()(app);
             }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(bindings: &[(ShortcutAction, Code)]) -> Shortcut {
+        let mut shortcut = Shortcut::empty();
+        for &(action, code) in bindings {
+            shortcut.bind(action, None, KeyBinding::new(code));
+        }
+        shortcut
+    }
+
+    #[test]
+    fn overrides_replace_actions() {
+        let game = overrides(&[(ShortcutAction::AddHit, Code::F1)]);
+        let mut category = Shortcut::empty();
+        category.bindings.insert(ShortcutAction::SubHit, Vec::new());
+
+        let active = Shortcut::default().with_overrides([&game, &category]);
+        assert!(active.bindings(ShortcutAction::AddHit) == [KeyBinding::new(Code::F1)]);
+        assert!(active.bindings(ShortcutAction::SubHit).is_empty());
+        assert!(active.bindings(ShortcutAction::Reset) == [KeyBinding::new(Code::Numpad5)]);
+    }
+
+    #[test]
+    fn overrides_take_keys_from_other_actions() {
+        let game = overrides(&[(ShortcutAction::AddHit, Code::Numpad8)]);
+        let active = Shortcut::default().with_overrides([&game]);
+        assert!(active.bindings(ShortcutAction::PrevSplit).is_empty());
+        let id = KeyBinding::new(Code::Numpad8).id();
+        assert!(active.action(id) == Some(ShortcutAction::AddHit));
+    }
+
+    #[test]
+    fn category_overrides_win_over_game_overrides() {
+        // Bound at different times, so neither override knew about the other.
+        let game = overrides(&[(ShortcutAction::Reset, Code::F1)]);
+        let category = overrides(&[(ShortcutAction::AddHit, Code::F1)]);
+        let active = Shortcut::default().with_overrides([&game, &category]);
+
+        let f1 = KeyBinding::new(Code::F1);
+        assert!(active.action(f1.id()) == Some(ShortcutAction::AddHit));
+        assert!(active.bindings(ShortcutAction::Reset).is_empty());
+        assert_eq!(active.all_bindings().filter(|&&b| b == f1).count(), 1);
+    }
+}
@@ -0,0 +1,68 @@
+use std::{fs::read_dir, path::Path};
+
+use crate::{files, get_config_path, Error};
+
+use super::{Record, Storage};
+
+/// One json file per record in the config directory. Files are replaced atomically and
+/// backed up, see `files::write`.
+pub struct JsonStorage;
+
+impl Storage for JsonStorage {
+    fn read(&self, record: &Record) -> Result<Option<String>, Error> {
+        let path = format!("{}/{}", get_config_path(), record.file());
+        match std::fs::read_to_string(&path) {
+            Ok(json) => Ok(Some(json)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::io(
+                format!("Could not read file \"{path}\""),
+                e.to_string(),
+            )),
+        }
+    }
+
+    fn write(&self, record: &Record, json: &str) -> Result<(), Error> {
+        match files::write(&record.file(), json) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::io(
+                format!(
+                    "Could not save file \"{}/{}\"",
+                    get_config_path(),
+                    record.file()
+                ),
+                e.to_string(),
+            )),
+        }
+    }
+
+    fn delete(&self, record: &Record) -> Result<(), Error> {
+        match files::remove(&record.file()) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::io(
+                format!(
+                    "Could not remove file \"{}/{}\"",
+                    get_config_path(),
+                    record.file()
+                ),
+                e.to_string(),
+            )),
+        }
+    }
+
+    fn records(&self) -> Result<Vec<Record>, Error> {
+        let config_path = get_config_path();
+        let mut records: Vec<Record> = [Record::Config, Record::Shortcuts]
+            .into_iter()
+            .filter(|record| Path::new(&format!("{config_path}/{}", record.file())).exists())
+            .collect();
+        for folder in ["games", "categories", "history"] {
+            let Ok(entries) = read_dir(format!("{config_path}/{folder}")) else {
+                continue;
+            };
+            records.extend(entries.flatten().filter_map(|entry| {
+                Record::from_file(&format!("{folder}/{}", entry.file_name().to_str()?))
+            }));
+        }
+        Ok(records)
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::Error;
+
+use super::{Record, Storage};
+
+/// Keeps records in memory only, for tests and for trying HitSplit without touching the
+/// saved data.
+#[derive(Default)]
+pub struct MemoryStorage {
+    records: Mutex<HashMap<Record, String>>,
+}
+
+impl MemoryStorage {
+    fn lock(&self) -> Result<MutexGuard<HashMap<Record, String>>, Error> {
+        match self.records.lock() {
+            Ok(records) => Ok(records),
+            Err(e) => Err(Error::io(
+                "Could not access the in-memory storage".to_string(),
+                e.to_string(),
+            )),
+        }
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, record: &Record) -> Result<Option<String>, Error> {
+        Ok(self.lock()?.get(record).cloned())
+    }
+
+    fn write(&self, record: &Record, json: &str) -> Result<(), Error> {
+        self.lock()?.insert(record.clone(), json.to_owned());
+        Ok(())
+    }
+
+    fn delete(&self, record: &Record) -> Result<(), Error> {
+        self.lock()?.remove(record);
+        Ok(())
+    }
+
+    fn records(&self) -> Result<Vec<Record>, Error> {
+        Ok(self.lock()?.keys().cloned().collect())
+    }
+}
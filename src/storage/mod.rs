@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use crate::{
+    logging,
+    run::history::{Attempt, History},
+    schema::{self, Versioned},
+    Error,
+};
+
+mod json;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use json::JsonStorage;
+pub use memory::MemoryStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+/// Environment variable choosing the storage backend: `json` (default), `memory` or,
+/// when built with the `sqlite` feature, `sqlite`.
+pub const STORAGE_VAR: &str = "HITSPLIT_STORAGE";
+
+/// A stored document. Documents are versioned json, see `schema`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Record {
+    Config,
+    Shortcuts,
+    Game(String),
+    Category(String),
+    History(String),
+}
+
+impl Record {
+    /// Path of the record in the json directory, relative to the config directory.
+    pub fn file(&self) -> String {
+        match self {
+            Record::Config => "config.json".to_owned(),
+            Record::Shortcuts => "shortcuts.json".to_owned(),
+            Record::Game(uuid) => format!("games/{uuid}.json"),
+            Record::Category(uuid) => format!("categories/{uuid}.json"),
+            Record::History(uuid) => format!("history/{uuid}.json"),
+        }
+    }
+
+    /// Record stored at `file`, a path returned by `Record::file`.
+    pub fn from_file(file: &str) -> Option<Self> {
+        let record = match file.strip_suffix(".json")?.split_once('/') {
+            None if file == "config.json" => Record::Config,
+            None if file == "shortcuts.json" => Record::Shortcuts,
+            Some(("games", uuid)) => Record::Game(uuid.to_owned()),
+            Some(("categories", uuid)) => Record::Category(uuid.to_owned()),
+            Some(("history", uuid)) => Record::History(uuid.to_owned()),
+            _ => return None,
+        };
+        Some(record)
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\"", self.file())
+    }
+}
+
+/// Where games, categories, config, shortcuts and history are kept.
+pub trait Storage: Send + Sync {
+    /// Contents of a record, or `None` if it was never written.
+    fn read(&self, record: &Record) -> Result<Option<String>, Error>;
+
+    fn write(&self, record: &Record, json: &str) -> Result<(), Error>;
+
+    fn delete(&self, record: &Record) -> Result<(), Error>;
+
+    /// Every stored record.
+    fn records(&self) -> Result<Vec<Record>, Error>;
+
+    /// Adds an attempt to the history of a category. Backends that store attempts apart
+    /// override it so long histories are not rewritten on every attempt.
+    fn record_attempt(&self, category_uuid: &str, attempt: &Attempt) -> Result<(), Error> {
+        let mut history = History::load(category_uuid.to_owned())?;
+        history.attempts.push(attempt.clone());
+        history.save()
+    }
+}
+
+static STORAGE: Mutex<Option<Arc<dyn Storage>>> = Mutex::new(None);
+
+fn open() -> Arc<dyn Storage> {
+    match std::env::var(STORAGE_VAR).as_deref() {
+        Ok("memory") => Arc::new(MemoryStorage::default()),
+        #[cfg(feature = "sqlite")]
+        Ok("sqlite") => match SqliteStorage::open() {
+            Ok(storage) => Arc::new(storage),
+            Err(_) => {
+                logging::warn("Using json files, as the SQLite database could not be opened");
+                Arc::new(JsonStorage)
+            }
+        },
+        _ => Arc::new(JsonStorage),
+    }
+}
+
+/// The storage backend of the active profile, chosen by `STORAGE_VAR` on first use.
+pub fn storage() -> Arc<dyn Storage> {
+    match STORAGE.lock() {
+        Ok(mut storage) => storage.get_or_insert_with(open).clone(),
+        Err(_) => open(),
+    }
+}
+
+/// Keeps the records of tests in memory and their log in a temporary folder, so the data
+/// of the user is never touched.
+#[cfg(test)]
+pub fn use_test_storage() {
+    let dir = std::env::temp_dir().join("hitsplit-tests");
+    crate::set_data_path(&dir.to_string_lossy());
+    if let Ok(mut storage) = STORAGE.lock() {
+        storage.get_or_insert_with(|| Arc::new(MemoryStorage::default()));
+    }
+}
+
+/// Closes the storage backend and forgets what was read from it, so the next access
+/// opens the one of the active profile. In-memory storage starts empty again.
+pub fn reopen() {
+    if let Ok(mut storage) = STORAGE.lock() {
+        *storage = None;
+    }
+    if let Ok(mut known) = KNOWN.lock() {
+        known.clear();
+    }
+}
+
+/// Contents of the records HitSplit last read or wrote, to notice changes made by other
+/// programs. Histories are left out, as they can be long and are not edited by hand.
+static KNOWN: LazyLock<Mutex<HashMap<Record, String>>> = LazyLock::new(Default::default);
+
+fn remember(record: &Record, json: &str) {
+    if matches!(record, Record::History(_)) {
+        return;
+    }
+    if let Ok(mut known) = KNOWN.lock() {
+        known.insert(record.clone(), json.to_owned());
+    }
+}
+
+fn known(record: &Record) -> Option<String> {
+    KNOWN.lock().ok()?.get(record).cloned()
+}
+
+/// Reads a record from the storage backend, remembering its contents.
+pub fn read(record: &Record) -> Result<Option<String>, Error> {
+    let json = storage().read(record)?;
+    if let Some(json) = &json {
+        logging::debug(&format!("Read {record}"));
+        remember(record, json);
+    }
+    Ok(json)
+}
+
+/// Writes a record to the storage backend, remembering its contents.
+pub fn write(record: &Record, json: &str) -> Result<(), Error> {
+    storage().write(record, json)?;
+    logging::debug(&format!("Wrote {record}"));
+    remember(record, json);
+    Ok(())
+}
+
+/// Deletes a record from the storage backend.
+pub fn delete(record: &Record) -> Result<(), Error> {
+    storage().delete(record)?;
+    logging::info(&format!("Deleted {record}"));
+    if let Ok(mut known) = KNOWN.lock() {
+        known.remove(record);
+    }
+    Ok(())
+}
+
+/// New contents of `record`, if another program changed it since HitSplit last read or
+/// wrote it. The change is not remembered until `acknowledge` is called.
+pub fn external_change(record: &Record) -> Result<Option<String>, Error> {
+    let Some(known) = known(record) else {
+        return Ok(None);
+    };
+    Ok(storage().read(record)?.filter(|json| *json != known))
+}
+
+/// Remembers contents found with `external_change`, so they are not reported again.
+pub fn acknowledge(record: &Record, json: &str) {
+    remember(record, json);
+}
+
+/// Whether `value` differs from the contents HitSplit last read or wrote for `record`.
+pub fn unsaved<T: Versioned>(record: &Record, value: &T) -> bool {
+    let known = known(record).and_then(|json| schema::from_str::<T>(&json).ok());
+    match (known, schema::to_string(value)) {
+        (Some(known), Ok(json)) => schema::to_string(&known).ok() != Some(json),
+        _ => true,
+    }
+}
+
+/// Uuids of the stored games.
+pub fn game_uuids() -> Result<Vec<String>, Error> {
+    Ok(storage()
+        .records()?
+        .into_iter()
+        .filter_map(|record| match record {
+            Record::Game(uuid) => Some(uuid),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Uuids of the stored categories.
+pub fn category_uuids() -> Result<Vec<String>, Error> {
+    Ok(storage()
+        .records()?
+        .into_iter()
+        .filter_map(|record| match record {
+            Record::Category(uuid) => Some(uuid),
+            _ => None,
+        })
+        .collect())
+}
@@ -0,0 +1,214 @@
+use std::sync::{Mutex, MutexGuard};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+use crate::{
+    get_config_path,
+    run::history::{Attempt, History},
+    schema::Versioned,
+    Error,
+};
+
+use super::{Record, Storage};
+
+/// Single database file in the config directory. Attempts are stored one per row, so
+/// recording an attempt does not rewrite the whole history of the category.
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+fn sqlite_error(message: &str, e: rusqlite::Error) -> Error {
+    Error::io(message.to_owned(), e.to_string())
+}
+
+impl SqliteStorage {
+    pub fn open() -> Result<Self, Error> {
+        let config_path = get_config_path();
+        if let Err(e) = std::fs::create_dir_all(&config_path) {
+            return Err(Error::io(
+                format!("Could not create config directory: \"{config_path}\""),
+                e.to_string(),
+            ));
+        }
+        let path = format!("{config_path}/hitsplit.db");
+        let connection = Connection::open(&path)
+            .map_err(|e| sqlite_error(&format!("Could not open database \"{path}\""), e))?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS records (
+                    file TEXT PRIMARY KEY,
+                    json TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS attempts (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    category_uuid TEXT NOT NULL,
+                    version INTEGER NOT NULL,
+                    json TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS attempts_category ON attempts (category_uuid);",
+            )
+            .map_err(|e| sqlite_error(&format!("Could not create tables in \"{path}\""), e))?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn lock(&self) -> Result<MutexGuard<Connection>, Error> {
+        match self.connection.lock() {
+            Ok(connection) => Ok(connection),
+            Err(e) => Err(Error::io(
+                "Could not access the database".to_string(),
+                e.to_string(),
+            )),
+        }
+    }
+
+    /// Builds the history document from the attempt rows. The document takes the oldest
+    /// version among them, so their migrations run on load.
+    fn read_history(&self, category_uuid: &str) -> Result<Option<String>, Error> {
+        let error = |e| sqlite_error(&format!("Could not read history of {category_uuid}"), e);
+        let connection = self.lock()?;
+        let mut statement = connection
+            .prepare("SELECT version, json FROM attempts WHERE category_uuid = ?1 ORDER BY id")
+            .map_err(error)?;
+        let rows = statement
+            .query_map(params![category_uuid], |row| {
+                Ok((row.get::<_, u64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        let Some(version) = rows.iter().map(|(version, _)| *version).min() else {
+            return Ok(None);
+        };
+        let attempts: Vec<String> = rows.into_iter().map(|(_, json)| json).collect();
+        Ok(Some(format!(
+            "{{\"version\":{version},\"category_uuid\":{},\"attempts\":[{}]}}",
+            Value::from(category_uuid),
+            attempts.join(",")
+        )))
+    }
+
+    fn write_history(&self, category_uuid: &str, json: &str) -> Result<(), Error> {
+        let history: Value = match serde_json::from_str(json) {
+            Ok(history) => history,
+            Err(e) => {
+                return Err(Error::parse(
+                    format!("Could not parse history of {category_uuid}"),
+                    e.to_string(),
+                ))
+            }
+        };
+        let version = history
+            .get("version")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+        let attempts = history
+            .get("attempts")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        let error = |e| sqlite_error(&format!("Could not save history of {category_uuid}"), e);
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction().map_err(error)?;
+        transaction
+            .execute(
+                "DELETE FROM attempts WHERE category_uuid = ?1",
+                params![category_uuid],
+            )
+            .map_err(error)?;
+        for attempt in attempts {
+            transaction
+                .execute(
+                    "INSERT INTO attempts (category_uuid, version, json) VALUES (?1, ?2, ?3)",
+                    params![category_uuid, version, attempt.to_string()],
+                )
+                .map_err(error)?;
+        }
+        transaction.commit().map_err(error)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn read(&self, record: &Record) -> Result<Option<String>, Error> {
+        if let Record::History(uuid) = record {
+            return self.read_history(uuid);
+        }
+        self.lock()?
+            .query_row(
+                "SELECT json FROM records WHERE file = ?1",
+                params![record.file()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| sqlite_error(&format!("Could not read {record}"), e))
+    }
+
+    fn write(&self, record: &Record, json: &str) -> Result<(), Error> {
+        if let Record::History(uuid) = record {
+            return self.write_history(uuid, json);
+        }
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO records (file, json) VALUES (?1, ?2)",
+                params![record.file(), json],
+            )
+            .map(|_| ())
+            .map_err(|e| sqlite_error(&format!("Could not save {record}"), e))
+    }
+
+    fn delete(&self, record: &Record) -> Result<(), Error> {
+        let connection = self.lock()?;
+        let result = match record {
+            Record::History(uuid) => connection.execute(
+                "DELETE FROM attempts WHERE category_uuid = ?1",
+                params![uuid],
+            ),
+            _ => connection.execute(
+                "DELETE FROM records WHERE file = ?1",
+                params![record.file()],
+            ),
+        };
+        result
+            .map(|_| ())
+            .map_err(|e| sqlite_error(&format!("Could not remove {record}"), e))
+    }
+
+    fn records(&self) -> Result<Vec<Record>, Error> {
+        let error = |e| sqlite_error("Could not list the stored records", e);
+        let connection = self.lock()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT file FROM records
+                UNION SELECT DISTINCT 'history/' || category_uuid || '.json' FROM attempts",
+            )
+            .map_err(error)?;
+        let files = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        Ok(files.iter().filter_map(|f| Record::from_file(f)).collect())
+    }
+
+    fn record_attempt(&self, category_uuid: &str, attempt: &Attempt) -> Result<(), Error> {
+        let json = match serde_json::to_string(attempt) {
+            Ok(json) => json,
+            Err(e) => {
+                return Err(Error::parse(
+                    format!("Could not serialize attempt of category {category_uuid}"),
+                    e.to_string(),
+                ))
+            }
+        };
+        self.lock()?
+            .execute(
+                "INSERT INTO attempts (category_uuid, version, json) VALUES (?1, ?2, ?3)",
+                params![category_uuid, History::version(), json],
+            )
+            .map(|_| ())
+            .map_err(|e| sqlite_error(&format!("Could not record attempt of {category_uuid}"), e))
+    }
+}
@@ -0,0 +1,150 @@
+use std::time::{Duration, Instant};
+
+use egui::Context;
+
+use crate::{
+    logging,
+    run::{category::Category, game::Game},
+    schema,
+    settings::config::Config,
+    storage::{self, Record},
+    Error, HitSplit,
+};
+
+/// How often the stored data is compared with the loaded one.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A loaded record that was changed by another program while it also had unsaved changes.
+#[derive(Clone)]
+pub struct Conflict {
+    pub record: Record,
+    /// Contents written by the other program.
+    pub json: String,
+    pub description: String,
+}
+
+/// Reloads the loaded config, game and category when another program changes them. If
+/// they have unsaved changes, a conflict is opened instead.
+pub fn check_external_changes(app: &mut HitSplit) -> Result<(), Error> {
+    if app.conflict.is_some() || app.last_external_check.elapsed() < CHECK_INTERVAL {
+        return Ok(());
+    }
+    app.last_external_check = Instant::now();
+
+    let mut records = vec![(Record::Config, "Settings".to_owned())];
+    if let Some(g) = &app.loaded_game {
+        records.push((Record::Game(g.uuid.clone()), format!("Game \"{}\"", g.name)));
+    }
+    if let Some(c) = &app.loaded_category {
+        records.push((
+            Record::Category(c.uuid.clone()),
+            format!("Category \"{}\"", c.name),
+        ));
+    }
+
+    for (record, description) in records {
+        let Some(json) = storage::external_change(&record)? else {
+            continue;
+        };
+        let unsaved = match &record {
+            Record::Config => storage::unsaved(&record, &app.config),
+            Record::Game(_) => app
+                .loaded_game
+                .as_ref()
+                .is_some_and(|g| storage::unsaved(&record, g)),
+            Record::Category(_) => app
+                .loaded_category
+                .as_ref()
+                .is_some_and(|c| storage::unsaved(&record, c)),
+            _ => false,
+        };
+        if unsaved {
+            logging::warn(&format!(
+                "{record} was changed by another program while it had unsaved changes"
+            ));
+            app.conflict = Some(Conflict {
+                record,
+                json,
+                description,
+            });
+            return Ok(());
+        }
+        reload(app, &record, &json)?;
+    }
+    Ok(())
+}
+
+/// Replaces the loaded data with the contents written by the other program.
+fn reload(app: &mut HitSplit, record: &Record, json: &str) -> Result<(), Error> {
+    storage::acknowledge(record, json);
+    logging::info(&format!("Reloaded {record}, changed by another program"));
+    let error =
+        |e: serde_json::Error| Error::parse(format!("Could not reload {record}"), e.to_string());
+    match record {
+        Record::Config => {
+            app.config = schema::from_str::<Config>(json).map_err(error)?;
+            app.chrono.set_format(&app.config.chrono_format);
+        }
+        Record::Game(_) => app.loaded_game = Some(schema::from_str::<Game>(json).map_err(error)?),
+        Record::Category(_) => {
+            let category = schema::from_str::<Category>(json).map_err(error)?;
+            app.num_splits_category = category.splits.len();
+            app.selected_split = app
+                .selected_split
+                .min(category.splits.len().saturating_sub(1));
+            app.loaded_category = Some(category);
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Keeps the loaded data, writing it over the contents of the other program.
+fn keep(app: &mut HitSplit, record: &Record) -> Result<(), Error> {
+    match record {
+        Record::Config => app.config.save(),
+        Record::Game(_) => app.loaded_game.as_ref().map_or(Ok(()), Game::save),
+        Record::Category(_) => app.loaded_category.as_ref().map_or(Ok(()), Category::save),
+        _ => Ok(()),
+    }
+}
+
+pub fn conflict_prompt(app: &mut HitSplit, ctx: &Context) {
+    let Some(conflict) = app.conflict.clone() else {
+        return;
+    };
+    let mut reload_clicked = false;
+    let mut keep_clicked = false;
+
+    egui::Window::new("Changed outside HitSplit")
+        .fixed_pos([10., 10.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Changed outside HitSplit");
+            ui.label(format!(
+                "{} was changed by another program, but it also has changes that are not saved.",
+                conflict.description
+            ));
+            ui.horizontal(|ui| {
+                if ui.small_button("Reload").clicked() {
+                    reload_clicked = true;
+                }
+                if ui.small_button("Keep mine").clicked() {
+                    keep_clicked = true;
+                }
+            });
+        });
+
+    let result = if reload_clicked {
+        reload(app, &conflict.record, &conflict.json)
+    } else if keep_clicked {
+        keep(app, &conflict.record)
+    } else {
+        return;
+    };
+    if let Err(e) = result {
+        app.errors.report(e);
+    }
+    app.conflict = None;
+}
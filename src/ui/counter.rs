@@ -2,7 +2,7 @@ use std::cmp::min;
 
 use egui::{Color32, Context, Sense};
 
-use crate::{settings::columns::Column, HitSplit};
+use crate::{run::assets, settings::columns::Column, HitSplit};
 
 pub fn counter(app: &mut HitSplit, ctx: &Context) {
     let bg = &app.config.background_color;
@@ -23,17 +23,18 @@ pub fn counter(app: &mut HitSplit, ctx: &Context) {
             if let Some(style) = ui.style_mut().text_styles.get_mut(&egui::TextStyle::Body) {
                 style.size = app.config.font_size;
             };
+            if app.hotkeys_locked {
+                ui.vertical_centered(|ui| {
+                    ui.colored_label(Color32::from_rgb(250, 8, 8), "🔒 Hotkeys locked");
+                });
+            }
             ui.vertical_centered(|ui| {
                 if let Some(game) = &app.loaded_game {
                     if let Some(img) = &game.icon_path {
-                        if let Some(path) = img.as_path().to_str() {
-                            ui.add(
-                                egui::Image::new(format!("file://{path}"))
-                                    .max_height(app.config.game_image_height),
-                            );
-                        } else {
-                            ui.colored_label(color, game.name.clone());
-                        }
+                        ui.add(
+                            egui::Image::new(assets::uri(img))
+                                .max_height(app.config.game_image_height),
+                        );
                     } else {
                         ui.colored_label(color, game.name.clone());
                     }
@@ -42,14 +43,10 @@ pub fn counter(app: &mut HitSplit, ctx: &Context) {
             ui.vertical_centered(|ui| {
                 if let Some(category) = &app.loaded_category {
                     if let Some(img) = &category.icon_path {
-                        if let Some(path) = img.as_path().to_str() {
-                            ui.add(
-                                egui::Image::new(format!("file://{path}"))
-                                    .max_height(app.config.category_image_height),
-                            );
-                        } else {
-                            ui.colored_label(color, category.name.clone());
-                        }
+                        ui.add(
+                            egui::Image::new(assets::uri(img))
+                                .max_height(app.config.category_image_height),
+                        );
                     } else {
                         ui.colored_label(color, category.name.clone());
                     }
@@ -109,9 +106,9 @@ pub fn counter(app: &mut HitSplit, ctx: &Context) {
                                 let mut label_color = color;
                                 if i <= app.selected_split {
                                     let color_array: [u8; 3];
-                                    if split.hits == 0 {
+                                    if split.total_hits() == 0 {
                                         color_array = app.config.text_color_nohit;
-                                    } else if split.hits < split.pb {
+                                    } else if split.total_hits() < split.pb {
                                         color_array = app.config.text_color_better;
                                     } else {
                                         color_array = app.config.text_color_worse;
@@ -124,14 +121,7 @@ pub fn counter(app: &mut HitSplit, ctx: &Context) {
                                 }
                                 body.row(app.config.font_size + 5.0, |mut row| {
                                     for column in app.config.columns.iter() {
-                                        column.body(
-                                            app,
-                                            i,
-                                            split,
-                                            label_color,
-                                            &app.config.chrono_format,
-                                            &mut row,
-                                        );
+                                        column.body(app, i, split, label_color, &mut row);
                                     }
                                 });
                             });
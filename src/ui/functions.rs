@@ -1,4 +1,10 @@
-use crate::{settings::shortcut::ShortcutAction, HitSplit};
+use egui::Color32;
+
+use crate::{
+    run::chrono::{ChronometerFormat, FORMAT_PRESETS},
+    settings::shortcut::{Capture, ShortcutAction, ShortcutTarget},
+    HitSplit,
+};
 
 pub fn image_button<'a>(
     image_src: impl Into<egui::ImageSource<'a>>,
@@ -13,27 +19,118 @@ pub fn image_button<'a>(
     )
 }
 
-pub fn shortcut_button(app: &mut HitSplit, ui: &mut egui::Ui, action: &ShortcutAction) {
-    let capturing: bool = app.capturing.as_ref().is_some_and(|c| c == action);
+const CAPTURE_LABEL: &str =
+    "Press a key or a combination to change shortcut. Click again to cancel.";
+
+fn toggle_capture(app: &mut HitSplit, capture: Capture) {
+    if app.capturing == Some(capture) {
+        app.capturing = None;
+    } else {
+        app.capturing = Some(capture);
+    }
+}
+
+/// Shows the bindings of an action in `target`. Clicking a binding captures a new one in its
+/// place, "x" unbinds it and "+" captures one more.
+pub fn shortcut_button(
+    app: &mut HitSplit,
+    ui: &mut egui::Ui,
+    target: ShortcutTarget,
+    action: &ShortcutAction,
+) {
+    let bindings = app
+        .shortcut_of(target)
+        .map(|s| s.bindings(*action).to_vec())
+        .unwrap_or_default();
+    let mut unbind = None;
 
-    let btn_label = if capturing {
-        "Press a key to change shortcut. Click again to cancel.".to_string()
-    } else if let Some(shortcut) = app.shortcut.as_ref() {
-        if let Some(code) = shortcut.0.get(action.to_usize()) {
-            code.to_string()
+    for (i, binding) in bindings.iter().enumerate() {
+        let capture = Capture {
+            target,
+            action: *action,
+            index: Some(i),
+        };
+        let btn_label = if app.capturing == Some(capture) {
+            CAPTURE_LABEL.to_owned()
         } else {
-            "None".to_owned()
+            binding.to_string()
+        };
+        if ui.button(btn_label).clicked() {
+            toggle_capture(app, capture);
+        }
+        if ui.small_button("x").clicked() {
+            unbind = Some(i);
         }
+    }
+    if bindings.is_empty() {
+        ui.label("None");
+    }
+
+    let capture = Capture {
+        target,
+        action: *action,
+        index: None,
+    };
+    let btn_label = if app.capturing == Some(capture) {
+        CAPTURE_LABEL
     } else {
-        "None".to_owned()
+        "+"
     };
     if ui.button(btn_label).clicked() {
-        if capturing {
-            app.capturing = None;
-        } else {
-            app.capturing = Some(*action);
+        toggle_capture(app, capture);
+    }
+
+    if let Some(i) = unbind {
+        if let Some(shortcut) = app.shortcut_mut(target) {
+            shortcut.unbind(*action, i);
+        }
+        app.manage_hotkeys();
+    }
+}
+
+/// Lets the user pick a preset or write a template. The format is only changed when the
+/// template is valid, otherwise the parse error is shown next to it.
+pub fn chrono_format_edit(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    format: &mut ChronometerFormat,
+) -> bool {
+    let id = ui.make_persistent_id(id_salt);
+    let mut template = ui
+        .data_mut(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| format.template().to_owned());
+    let mut changed = false;
+
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(format.text())
+        .show_ui(ui, |ui| {
+            for (name, preset) in FORMAT_PRESETS {
+                if ui
+                    .selectable_label(format.template() == preset, name)
+                    .clicked()
+                {
+                    if let Ok(f) = ChronometerFormat::parse(preset) {
+                        *format = f;
+                        preset.clone_into(&mut template);
+                        changed = true;
+                    }
+                }
+            }
+        });
+    let res = ui.add(egui::TextEdit::singleline(&mut template).desired_width(160.0));
+    match ChronometerFormat::parse(&template) {
+        Ok(f) => {
+            if res.changed() {
+                *format = f;
+                changed = true;
+            }
+        }
+        Err(e) => {
+            ui.colored_label(Color32::from_rgb(250, 8, 8), e);
         }
     }
+    ui.data_mut(|d| d.insert_temp(id, template));
+    changed
 }
 
 pub fn numeric_edit_field_u64(ui: &mut egui::Ui, value: &mut u64, width: f32) -> egui::Response {
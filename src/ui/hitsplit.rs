@@ -1,21 +1,40 @@
 use super::{
+    conflict::{check_external_changes, conflict_prompt, Conflict},
     counter::counter,
+    notifications::ErrorQueue,
     panels::{bottom_panel, left_panel, list::list, settings::configuration, Pages},
-    ChangeImage,
+    ChangeImage, FileAction, VERSION,
 };
 use crate::{
-    run::{category::Category, chrono::Chronometer, game::Game},
+    files::Backup,
+    formats::{
+        bundle::{BundleExport, BundleImport},
+        hitcountermanager::HcmImport,
+        livesplit::LssImport,
+    },
+    logging,
+    run::{
+        assets, category::Category, chrono::Chronometer, game::Game, integrity::Problem,
+        manager::store_split_time,
+    },
+    server::{server_handler, Server},
     settings::{
         config::Config,
-        shortcut::{shortcut_handler, Shortcut, ShortcutAction},
+        profile::{active_profile, remember_profile, set_profile},
+        shortcut::{
+            shortcut_handler, Capture, KeyBinding, Shortcut, ShortcutAction, ShortcutTarget,
+        },
     },
-    Error,
+    storage, Error,
 };
 use eframe::{egui::Visuals, Storage};
 use egui::Vec2;
 use egui_file::FileDialog;
-use global_hotkey::{hotkey::Code, GlobalHotKeyManager};
-use std::{path::PathBuf, time::Duration};
+use global_hotkey::GlobalHotKeyManager;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
 
 pub struct HitSplit {
     pub config: Config,
@@ -26,6 +45,8 @@ pub struct HitSplit {
     pub add_game_open: bool,
     pub add_game_empty: bool,
     pub modify_game_open: bool,
+    pub add_profile_name: String,
+    pub add_profile_open: bool,
     pub add_category_name: String,
     pub add_category_open: bool,
     pub add_category_empty: bool,
@@ -37,12 +58,37 @@ pub struct HitSplit {
     pub selected_split: usize,
     pub show_config: bool,
     pub hotkey_manager: Option<GlobalHotKeyManager>,
-    pub capturing: Option<ShortcutAction>,
+    /// Bindings registered as hotkeys, which hotkey events are resolved with.
+    pub registered_shortcut: Option<Shortcut>,
+    /// Set by the lock action. Other hotkeys are ignored while it is set.
+    pub hotkeys_locked: bool,
+    pub server: Option<Server>,
+    pub capturing: Option<Capture>,
     pub opened_file: Option<PathBuf>,
     pub open_file_dialog: Option<FileDialog>,
     pub change_image: Option<ChangeImage>,
+    pub file_action: Option<FileAction>,
+    pub file_action_dialog: Option<FileDialog>,
+    pub lss_import: Option<LssImport>,
+    pub hcm_import: Option<HcmImport>,
+    pub bundle_import: Option<BundleImport>,
+    pub bundle_export: Option<BundleExport>,
+    /// Game that imported categories are added to. `None` creates a new game.
+    pub import_game: Option<String>,
+    /// Backups listed in the restore browser, which is open while this is set.
+    pub backups: Option<Vec<Backup>>,
+    /// Problems found by the data check and whether they are selected to be fixed. The
+    /// check window is open while this is set.
+    pub data_check: Option<Vec<(Problem, bool)>>,
+    /// Lines shown in the log viewer, which is open while this is set.
+    pub log_lines: Option<Vec<String>>,
+    /// Overrides edited in the shortcut overrides window, which is open while this is set.
+    pub shortcut_overrides: Option<ShortcutTarget>,
+    pub conflict: Option<Conflict>,
+    pub last_external_check: Instant,
     pub chrono: Chronometer,
-    pub error: Error,
+    pub attempt_started: Option<SystemTime>,
+    pub errors: ErrorQueue,
 }
 
 impl Clone for HitSplit {
@@ -56,6 +102,8 @@ impl Clone for HitSplit {
             add_game_open: self.add_game_open,
             add_game_empty: self.add_game_empty,
             modify_game_open: self.modify_game_open,
+            add_profile_name: self.add_profile_name.clone(),
+            add_profile_open: self.add_profile_open,
             add_category_name: self.add_category_name.clone(),
             add_category_open: self.add_category_open,
             add_category_empty: self.add_category_empty,
@@ -67,12 +115,29 @@ impl Clone for HitSplit {
             selected_split: self.selected_split,
             show_config: self.show_config,
             hotkey_manager: None,
+            registered_shortcut: None,
+            hotkeys_locked: self.hotkeys_locked,
+            server: None,
             capturing: self.capturing,
             opened_file: self.opened_file.clone(),
             open_file_dialog: None,
             change_image: None,
-            chrono: self.chrono,
-            error: self.error.clone(),
+            file_action: None,
+            file_action_dialog: None,
+            lss_import: None,
+            hcm_import: None,
+            bundle_import: None,
+            bundle_export: None,
+            import_game: self.import_game.clone(),
+            backups: self.backups.clone(),
+            data_check: self.data_check.clone(),
+            log_lines: self.log_lines.clone(),
+            shortcut_overrides: self.shortcut_overrides,
+            conflict: self.conflict.clone(),
+            last_external_check: self.last_external_check,
+            chrono: self.chrono.clone(),
+            attempt_started: self.attempt_started,
+            errors: self.errors.clone(),
         }
     }
 }
@@ -88,6 +153,8 @@ impl Default for HitSplit {
             add_game_open: false,
             add_game_empty: false,
             modify_game_open: false,
+            add_profile_name: "".to_string(),
+            add_profile_open: false,
             add_category_name: "".to_string(),
             add_category_open: false,
             add_category_empty: false,
@@ -99,49 +166,186 @@ impl Default for HitSplit {
             selected_split: 0,
             show_config: true,
             hotkey_manager: None,
+            registered_shortcut: None,
+            hotkeys_locked: false,
+            server: None,
             capturing: None,
             opened_file: None,
             open_file_dialog: None,
             change_image: None,
-            chrono: Chronometer::new(crate::run::chrono::ChronometerFormat::HHMMSSX),
-            error: Error::None,
+            file_action: None,
+            file_action_dialog: None,
+            lss_import: None,
+            hcm_import: None,
+            bundle_import: None,
+            bundle_export: None,
+            import_game: None,
+            backups: None,
+            data_check: None,
+            log_lines: None,
+            shortcut_overrides: None,
+            conflict: None,
+            last_external_check: Instant::now(),
+            chrono: Chronometer::new(Default::default()),
+            attempt_started: None,
+            errors: ErrorQueue::default(),
         }
     }
 }
 
 impl HitSplit {
-    fn add_hotkey(&mut self, code: Code) {
-        match self.hotkey_manager.as_ref() {
-            Some(mngr) => {
-                if let Err(e) = mngr.register(Shortcut::code_to_hotkey(code)) {
-                    self.error =
-                        Error::new(format!("Failed to register hotkey {}", code), e.to_string());
-                }
+    fn add_hotkey(&mut self, binding: KeyBinding) {
+        // Without a manager, the error was already reported by `manage_hotkeys`.
+        if let Some(mngr) = self.hotkey_manager.as_ref() {
+            if let Err(e) = mngr.register(binding.to_hotkey()) {
+                self.errors.report(Error::hotkey(
+                    format!("Failed to register hotkey {binding}"),
+                    e.to_string(),
+                ));
             }
-            None => {
-                self.error = Error::new("Failed to get hotkey manager".to_string(), "".to_string())
-            }
-        };
+        }
+    }
+
+    /// Bindings of `target`, if it is loaded. Games and categories without overrides have
+    /// none.
+    pub fn shortcut_of(&self, target: ShortcutTarget) -> Option<&Shortcut> {
+        match target {
+            ShortcutTarget::Global => self.shortcut.as_ref(),
+            ShortcutTarget::Game => self
+                .loaded_game
+                .as_ref()
+                .and_then(|g| g.shortcut_overrides.as_ref()),
+            ShortcutTarget::Category => self
+                .loaded_category
+                .as_ref()
+                .and_then(|c| c.shortcut_overrides.as_ref()),
+        }
+    }
+
+    /// Bindings of `target` to edit, if it is loaded. Overrides are created empty for a game
+    /// or category without them.
+    pub fn shortcut_mut(&mut self, target: ShortcutTarget) -> Option<&mut Shortcut> {
+        match target {
+            ShortcutTarget::Global => self.shortcut.as_mut(),
+            ShortcutTarget::Game => self
+                .loaded_game
+                .as_mut()
+                .map(|g| g.shortcut_overrides.get_or_insert_with(Shortcut::empty)),
+            ShortcutTarget::Category => self
+                .loaded_category
+                .as_mut()
+                .map(|c| c.shortcut_overrides.get_or_insert_with(Shortcut::empty)),
+        }
+    }
+
+    /// Bindings the actions of `target` have when they are not overridden.
+    pub fn inherited_shortcut(&self, target: ShortcutTarget) -> Option<Shortcut> {
+        match target {
+            ShortcutTarget::Global => None,
+            ShortcutTarget::Game => self.shortcut.clone(),
+            ShortcutTarget::Category => self
+                .shortcut
+                .as_ref()
+                .map(|sc| sc.with_overrides(self.shortcut_of(ShortcutTarget::Game))),
+        }
     }
 
-    fn manage_hotkeys(&mut self) {
+    /// Global bindings with the overrides of the loaded game and then of the loaded category.
+    pub fn active_shortcut(&self) -> Option<Shortcut> {
+        self.shortcut.as_ref().map(|sc| {
+            sc.with_overrides(
+                [ShortcutTarget::Game, ShortcutTarget::Category]
+                    .into_iter()
+                    .filter_map(|target| self.shortcut_of(target)),
+            )
+        })
+    }
+
+    /// Registers the bindings of `active_shortcut` as hotkeys, replacing the registered ones.
+    pub fn manage_hotkeys(&mut self) {
         match GlobalHotKeyManager::new() {
             Ok(mngr) => self.hotkey_manager = Some(mngr),
-            Err(_) => self.hotkey_manager = None,
+            Err(e) => {
+                self.hotkey_manager = None;
+                self.errors.report(Error::hotkey(
+                    "Failed to get hotkey manager".to_string(),
+                    e.to_string(),
+                ));
+            }
         };
-        if let Some(sc) = self.shortcut.clone() {
-            sc.0.iter().for_each(|&c| self.add_hotkey(c));
+        self.registered_shortcut = self.active_shortcut();
+        if let Some(sc) = self.registered_shortcut.clone() {
+            sc.all_bindings()
+                .for_each(|&binding| self.add_hotkey(binding));
         }
     }
 
+    /// Starts, restarts or stops the TCP server to match the config.
+    pub fn manage_server(&mut self, ctx: &egui::Context) {
+        if !self.config.tcp_server {
+            self.server = None;
+        } else if self
+            .server
+            .as_ref()
+            .is_none_or(|s| s.port != self.config.tcp_server_port)
+        {
+            self.server = None;
+            match Server::start(self.config.tcp_server_port, ctx.clone()) {
+                Ok(server) => self.server = Some(server),
+                Err(e) => self.errors.report(e),
+            }
+        }
+    }
+
+    /// Saves the open data and loads the config, shortcuts and games of `profile`.
+    pub fn switch_profile(&mut self, profile: &str, ctx: &egui::Context) {
+        self.save_files();
+        if let Err(e) = set_profile(profile).and_then(|_| remember_profile()) {
+            self.errors.report(e);
+            return;
+        }
+        storage::reopen();
+        logging::info(&format!("Switched to profile \"{profile}\""));
+        self.config = Config::load().unwrap_or_default();
+        logging::set_level(self.config.log_level);
+        self.shortcut = Some(Shortcut::load().unwrap_or_default());
+        self.loaded_game = None;
+        self.loaded_category = None;
+        self.num_splits_category = 0;
+        self.selected_split = 0;
+        self.conflict = None;
+        self.chrono.reset();
+        self.chrono.set_format(&self.config.chrono_format);
+        self.attempt_started = None;
+        if let Err(e) = assets::migrate() {
+            self.errors.report(e);
+        }
+        self.manage_hotkeys();
+        self.manage_server(ctx);
+        ctx.set_visuals(if self.config.dark_mode {
+            Visuals::dark()
+        } else {
+            Visuals::light()
+        });
+    }
+
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         let mut app: HitSplit = HitSplit {
             config: Config::load().unwrap_or_default(),
             shortcut: Some(Shortcut::load().unwrap_or_default()),
             ..Default::default()
         };
+        logging::set_level(app.config.log_level);
+        logging::info(&format!(
+            "HitSplit {VERSION} started with profile \"{}\"",
+            active_profile()
+        ));
         app.chrono.set_format(&app.config.chrono_format);
+        if let Err(e) = assets::migrate() {
+            app.errors.report(e);
+        }
         app.manage_hotkeys();
+        app.manage_server(&cc.egui_ctx);
 
         cc.egui_ctx.set_visuals(if app.config.dark_mode {
             Visuals::dark()
@@ -153,23 +357,23 @@ impl HitSplit {
         app
     }
 
-    fn save_files(&mut self) {
+    pub fn save_files(&mut self) {
         if let Err(e) = self.config.save() {
-            self.error = e;
+            self.errors.report(e);
         }
         if let Some(s) = &self.shortcut {
             if let Err(e) = s.save() {
-                self.error = e;
+                self.errors.report(e);
             }
         }
         if let Some(g) = &self.loaded_game {
             if let Err(e) = g.save() {
-                self.error = e;
+                self.errors.report(e);
             }
         }
         if let Some(c) = &self.loaded_category {
             if let Err(e) = c.save() {
-                self.error = e;
+                self.errors.report(e);
             }
         }
     }
@@ -177,7 +381,8 @@ impl HitSplit {
 
 impl eframe::App for HitSplit {
     fn save(&mut self, _storage: &mut dyn Storage) {
-        if self.config.autosave {
+        // Saving would overwrite the changes of the other program before the user chooses.
+        if self.config.autosave && self.conflict.is_none() {
             self.save_files();
         }
     }
@@ -189,6 +394,7 @@ impl eframe::App for HitSplit {
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.config.dark_mode = ctx.style().visuals.dark_mode;
+        logging::set_level(self.config.log_level);
         if self.config.always_on_top {
             ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
                 egui::WindowLevel::AlwaysOnTop,
@@ -199,23 +405,32 @@ impl eframe::App for HitSplit {
             ));
         }
 
+        // Loading another game or category, or editing bindings, changes the hotkeys.
+        if self.active_shortcut() != self.registered_shortcut {
+            self.manage_hotkeys();
+        }
+
         if self.capturing.is_none() {
             match shortcut_handler(self) {
                 Ok(_) => (),
-                Err(e) => self.error = e,
+                Err(e) => self.errors.report(e),
             };
         }
 
-        if let Some(category) = self.loaded_category.as_mut() {
-            if let Some(split) = category.splits.get_mut(self.selected_split) {
-                match self.chrono.get_time() {
-                    Ok(rt) => split.real_time = rt,
-                    Err(e) => self.error = e,
-                };
-            }
+        if let Err(e) = server_handler(self) {
+            self.errors.report(e);
+        }
+
+        if let Err(e) = check_external_changes(self) {
+            self.errors.report(e);
+        }
+
+        if let Err(e) = store_split_time(self) {
+            self.errors.report(e);
         }
 
         counter(self, ctx);
+        conflict_prompt(self, ctx);
 
         if self.show_config {
             ctx.show_viewport_immediate(
@@ -226,9 +441,14 @@ impl eframe::App for HitSplit {
                     .with_inner_size(Vec2::new(800.0, 800.0))
                     .with_min_inner_size(Vec2::new(650.0, 600.0)),
                 move |ctx, _class| {
-                    if let Some(sa) = &self.capturing.clone() {
-                        if let Some(key) = ctx.input(|i| i.keys_down.clone().into_iter().last()) {
-                            ShortcutAction::change_shortcut(self, sa, &key);
+                    if let Some(capture) = self.capturing {
+                        if let Some(binding) = ctx.input(|i| {
+                            i.keys_down
+                                .iter()
+                                .last()
+                                .map(|key| KeyBinding::from_key(key, &i.modifiers))
+                        }) {
+                            ShortcutAction::change_shortcut(self, capture, binding);
                             self.manage_hotkeys();
                             self.capturing = None;
                         }
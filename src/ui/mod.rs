@@ -1,8 +1,12 @@
+pub mod conflict;
 pub mod counter;
 pub mod functions;
 pub mod hitsplit;
+pub mod notifications;
 pub mod panels;
 
+use crate::formats::csv::CsvTable;
+
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(Clone, Eq, PartialEq, PartialOrd, Ord)]
@@ -11,3 +15,16 @@ pub enum ChangeImage {
     Category,
     Split(String),
 }
+
+/// Pending action for the file chosen in `HitSplit::file_action_dialog`.
+#[derive(Clone, Eq, PartialEq)]
+pub enum FileAction {
+    ImportLss,
+    ExportLss,
+    ImportHcm,
+    ExportHcm,
+    ExportSplitsIo,
+    ExportCsv(CsvTable),
+    ImportBundle,
+    ExportBundle,
+}
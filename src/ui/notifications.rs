@@ -0,0 +1,164 @@
+use std::{collections::VecDeque, time::SystemTime};
+
+use egui::{Color32, Context};
+
+use crate::{formats::rfc3339, Error, HitSplit};
+
+/// Errors kept in the notification list. Older ones are dropped.
+pub const MAX_NOTIFICATIONS: usize = 50;
+
+const ERROR_COLOR: Color32 = Color32::from_rgb(250, 8, 8);
+
+/// A reported error, shown in the manager until it is dismissed.
+#[derive(Clone)]
+pub struct Notification {
+    pub error: Error,
+    /// When it was last reported.
+    pub time: SystemTime,
+    /// Times it was reported in a row, as some errors repeat on every frame.
+    pub count: u32,
+}
+
+impl Notification {
+    pub fn new(error: Error) -> Self {
+        Self {
+            error,
+            time: SystemTime::now(),
+            count: 1,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        match self.count {
+            1 => format!("{}: {}", self.error.kind(), self.error),
+            count => format!("{}: {} ({count} times)", self.error.kind(), self.error),
+        }
+    }
+}
+
+/// Reported errors, oldest first.
+#[derive(Clone, Default)]
+pub struct ErrorQueue {
+    notifications: VecDeque<Notification>,
+    /// Whether the error list is open in the manager.
+    pub open: bool,
+}
+
+impl ErrorQueue {
+    /// Adds an error to the queue. An error equal to the newest one is counted instead,
+    /// and the oldest are dropped past `MAX_NOTIFICATIONS`.
+    pub fn report(&mut self, error: Error) {
+        if let Some(last) = self.notifications.back_mut().filter(|n| n.error == error) {
+            last.count += 1;
+            last.time = SystemTime::now();
+            return;
+        }
+        self.notifications.push_back(Notification::new(error));
+        if self.notifications.len() > MAX_NOTIFICATIONS {
+            self.notifications.pop_front();
+        }
+    }
+
+    pub fn newest(&self) -> Option<&Notification> {
+        self.notifications.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.notifications.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifications.is_empty()
+    }
+
+    pub fn dismiss(&mut self, index: usize) {
+        self.notifications.remove(index);
+    }
+
+    pub fn dismiss_newest(&mut self) {
+        self.notifications.pop_back();
+    }
+
+    pub fn clear(&mut self) {
+        self.notifications.clear();
+    }
+}
+
+/// Shows the newest error and buttons to dismiss it or open the whole list.
+pub fn error_bar(app: &mut HitSplit, ui: &mut egui::Ui) {
+    let Some(summary) = app.errors.newest().map(Notification::summary) else {
+        return;
+    };
+    ui.horizontal(|ui| {
+        if ui.small_button("Dismiss").clicked() {
+            app.errors.dismiss_newest();
+        }
+        if ui
+            .small_button(format!("All errors ({})", app.errors.len()))
+            .clicked()
+        {
+            app.errors.open = true;
+        }
+        ui.colored_label(ERROR_COLOR, summary);
+    });
+}
+
+/// Lists the reported errors, newest first, with the error given by the system or library.
+pub fn error_list(app: &mut HitSplit, ctx: &Context) {
+    if !app.errors.open {
+        return;
+    }
+    let mut dismiss = None;
+    let mut dismiss_all = false;
+    let mut close = false;
+
+    egui::Window::new("Errors")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Errors");
+            if app.errors.is_empty() {
+                ui.label("There are no errors.");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    app.errors.notifications.iter().enumerate().rev().for_each(
+                        |(i, notification)| {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("Dismiss").clicked() {
+                                    dismiss = Some(i);
+                                }
+                                ui.label(rfc3339(notification.time));
+                                ui.colored_label(ERROR_COLOR, notification.summary());
+                            });
+                            if let Some(reported_error) = notification.error.reported_error() {
+                                egui::CollapsingHeader::new("Details")
+                                    .id_salt(i)
+                                    .show(ui, |ui| ui.label(reported_error));
+                            }
+                        },
+                    );
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.small_button("Dismiss all").clicked() {
+                    dismiss_all = true;
+                }
+                if ui.small_button("Close").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if let Some(i) = dismiss {
+        app.errors.dismiss(i);
+    }
+    if dismiss_all {
+        app.errors.clear();
+    }
+    if close || dismiss_all {
+        app.errors.open = false;
+    }
+}
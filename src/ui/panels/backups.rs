@@ -0,0 +1,91 @@
+use egui::Context;
+
+use crate::{
+    files::Backup,
+    formats::rfc3339,
+    logging,
+    run::{category::Category, game::Game},
+    settings::{config::Config, shortcut::Shortcut},
+    Error, HitSplit,
+};
+
+/// Lists the backups of the config directory and restores the chosen one.
+pub fn restore_backup_browser(app: &mut HitSplit, ctx: &Context) {
+    let Some(backups) = &app.backups else {
+        return;
+    };
+    let mut restore = None;
+    let mut close = false;
+
+    egui::Window::new("Restore from backup")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Restore from backup");
+            if backups.is_empty() {
+                ui.label("There are no backups yet.");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("backups").striped(true).show(ui, |ui| {
+                        backups.iter().for_each(|backup| {
+                            ui.label(backup.description());
+                            ui.label(rfc3339(backup.created));
+                            if ui.small_button("Restore").clicked() {
+                                restore = Some(backup.clone());
+                            }
+                            ui.end_row();
+                        });
+                    });
+                });
+            ui.separator();
+            if ui.small_button("Close").clicked() {
+                close = true;
+            }
+        });
+
+    if let Some(backup) = restore {
+        if let Err(e) = restore_backup(app, &backup) {
+            app.errors.report(e);
+        }
+        app.backups = None;
+    } else if close {
+        app.backups = None;
+    }
+}
+
+/// Restores `backup` and reloads the file if it is in use, so it is not saved over.
+fn restore_backup(app: &mut HitSplit, backup: &Backup) -> Result<(), Error> {
+    backup.restore()?;
+    logging::info(&format!(
+        "Restored {} from the backup of {}",
+        backup.description(),
+        rfc3339(backup.created)
+    ));
+    let uuid = |folder: &str| {
+        backup
+            .file
+            .strip_prefix(folder)?
+            .strip_suffix(".json")
+            .map(str::to_owned)
+    };
+    if backup.file == "config.json" {
+        app.config = Config::load()?;
+    } else if backup.file == "shortcuts.json" {
+        app.shortcut = Some(Shortcut::load()?);
+        app.manage_hotkeys();
+    } else if let Some(uuid) = uuid("games/") {
+        if app.loaded_game.as_ref().is_some_and(|g| g.uuid == uuid) {
+            app.loaded_game = Some(Game::load(uuid)?);
+        }
+    } else if let Some(uuid) = uuid("categories/") {
+        if app.loaded_category.as_ref().is_some_and(|c| c.uuid == uuid) {
+            let category = Category::load(uuid)?;
+            app.num_splits_category = category.splits.len();
+            app.loaded_category = Some(category);
+        }
+    }
+    Ok(())
+}
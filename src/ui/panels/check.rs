@@ -0,0 +1,97 @@
+use egui::Context;
+
+use crate::{
+    run::{
+        category::Category,
+        game::Game,
+        integrity::{check, Problem},
+    },
+    settings::config::Config,
+    Error, HitSplit,
+};
+
+/// Saves the open data and checks the stored data, opening the check window.
+pub fn open_data_check(app: &mut HitSplit) {
+    app.save_files();
+    match check() {
+        Ok(problems) => {
+            app.data_check = Some(problems.into_iter().map(|p| (p, true)).collect());
+        }
+        Err(e) => app.errors.report(e),
+    }
+}
+
+/// Lists the problems found by the data check and fixes the selected ones.
+pub fn data_check_window(app: &mut HitSplit, ctx: &Context) {
+    let Some(problems) = &mut app.data_check else {
+        return;
+    };
+    let mut fix = false;
+    let mut close = false;
+
+    egui::Window::new("Check data")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Check data");
+            if problems.is_empty() {
+                ui.label("No problems found.");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    problems.iter_mut().for_each(|(problem, selected)| {
+                        ui.add_enabled(
+                            problem.fixable(),
+                            egui::Checkbox::new(selected, problem.description()),
+                        );
+                    });
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if problems
+                    .iter()
+                    .any(|(p, selected)| *selected && p.fixable())
+                    && ui.small_button("Fix selected").clicked()
+                {
+                    fix = true;
+                }
+                if ui.small_button("Close").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if fix {
+        let selected: Vec<Problem> = problems
+            .iter()
+            .filter(|(p, selected)| *selected && p.fixable())
+            .map(|(p, _)| p.clone())
+            .collect();
+        if let Err(e) = repair(app, &selected) {
+            app.errors.report(e);
+        }
+        // Checks again, so what could not be fixed is still listed.
+        open_data_check(app);
+    } else if close {
+        app.data_check = None;
+    }
+}
+
+/// Fixes `problems` and reloads the open data, so it is not saved over.
+fn repair(app: &mut HitSplit, problems: &[Problem]) -> Result<(), Error> {
+    for problem in problems {
+        problem.repair()?;
+    }
+    app.config = Config::load()?;
+    if let Some(uuid) = app.loaded_game.as_ref().map(|g| g.uuid.clone()) {
+        app.loaded_game = Some(Game::load(uuid)?);
+    }
+    if let Some(uuid) = app.loaded_category.as_ref().map(|c| c.uuid.clone()) {
+        let category = Category::load(uuid)?;
+        app.num_splits_category = category.splits.len();
+        app.loaded_category = Some(category);
+    }
+    Ok(())
+}
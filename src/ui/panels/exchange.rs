@@ -0,0 +1,567 @@
+use std::{ffi::OsStr, path::Path};
+
+use egui::Context;
+use egui_file::FileDialog;
+use uuid::Uuid;
+
+use crate::{
+    formats::{
+        bundle::{export_bundle, BundleImport},
+        csv::export_csv,
+        hitcountermanager::{export_hcm, HcmImport},
+        livesplit::{export_lss, LssImport},
+        splitsio::export_splitsio,
+    },
+    run::{
+        category::Category,
+        chrono::duration_chrono_format,
+        game::{Game, SmallGame},
+    },
+    ui::FileAction,
+    Error, HitSplit,
+};
+
+/// Opens a dialog to choose a file with the given extension for `action`.
+pub fn open_file_action(app: &mut HitSplit, action: FileAction, extension: &'static str) {
+    let filter = Box::new({
+        move |path: &Path| -> bool { path.extension() == Some(OsStr::new(extension)) }
+    });
+    let mut dialog = FileDialog::open_file(None).show_files_filter(filter);
+    dialog.open();
+    app.file_action = Some(action);
+    app.file_action_dialog = Some(dialog);
+}
+
+/// Dialog to choose where to write an exported file, starting with `filename`.
+pub fn save_file_dialog(filename: String) -> FileDialog {
+    let mut dialog = FileDialog::save_file(None).default_filename(filename);
+    dialog.open();
+    dialog
+}
+
+pub fn file_action_dialog(app: &mut HitSplit, ctx: &Context) {
+    let path = match &mut app.file_action_dialog {
+        Some(dialog) => {
+            if !dialog.show(ctx).selected() {
+                if !dialog.visible() {
+                    app.file_action_dialog = None;
+                    app.file_action = None;
+                }
+                return;
+            }
+            dialog.path().map(Path::to_path_buf)
+        }
+        None => return,
+    };
+    app.file_action_dialog = None;
+
+    if let (Some(mut path), Some(action)) = (path, app.file_action.take()) {
+        match action {
+            FileAction::ImportLss => match LssImport::load(&path) {
+                Ok(import) => {
+                    app.import_game = app
+                        .config
+                        .game_list
+                        .iter()
+                        .find(|g| g.name == import.game_name)
+                        .map(|g| g.uuid.clone());
+                    app.lss_import = Some(import);
+                }
+                Err(e) => app.errors.report(e),
+            },
+            FileAction::ExportLss => {
+                if path.extension() != Some(OsStr::new("lss")) {
+                    path.set_extension("lss");
+                }
+                if let (Some(game), Some(category)) = (&app.loaded_game, &app.loaded_category) {
+                    if let Err(e) = export_lss(&path, game, category) {
+                        app.errors.report(e);
+                    }
+                }
+            }
+            FileAction::ExportSplitsIo => {
+                if path.extension() != Some(OsStr::new("json")) {
+                    path.set_extension("json");
+                }
+                if let (Some(game), Some(category)) = (&app.loaded_game, &app.loaded_category) {
+                    if let Err(e) = export_splitsio(&path, game, category, &app.config.runner_name)
+                    {
+                        app.errors.report(e);
+                    }
+                }
+            }
+            FileAction::ExportCsv(table) => {
+                if path.extension() != Some(OsStr::new("csv")) {
+                    path.set_extension("csv");
+                }
+                if let Some(category) = &app.loaded_category {
+                    if let Err(e) = export_csv(&path, category, table, &app.config.chrono_format) {
+                        app.errors.report(e);
+                    }
+                }
+            }
+            FileAction::ExportBundle => {
+                if path.extension() != Some(OsStr::new("zip")) {
+                    path.set_extension("zip");
+                }
+                if let Err(e) = export_game_bundle(app, &path) {
+                    app.errors.report(e);
+                }
+            }
+            FileAction::ImportBundle => match BundleImport::load(&path) {
+                Ok(import) => app.bundle_import = Some(import),
+                Err(e) => app.errors.report(e),
+            },
+            FileAction::ImportHcm => match HcmImport::load(&path) {
+                Ok(import) => {
+                    app.import_game = app.loaded_game.as_ref().map(|g| g.uuid.clone());
+                    app.hcm_import = Some(import);
+                }
+                Err(e) => app.errors.report(e),
+            },
+            FileAction::ExportHcm => {
+                if path.extension() != Some(OsStr::new("xml")) {
+                    path.set_extension("xml");
+                }
+                if let Err(e) = export_game_hcm(app, &path) {
+                    app.errors.report(e);
+                }
+            }
+        }
+    }
+}
+
+/// Exports every category of the loaded game.
+fn export_game_hcm(app: &HitSplit, path: &Path) -> Result<(), Error> {
+    let categories = game_categories(app, |_| true)?;
+    export_hcm(path, &categories)
+}
+
+fn export_game_bundle(app: &mut HitSplit, path: &Path) -> Result<(), Error> {
+    let export = match app.bundle_export.take() {
+        Some(e) => e,
+        None => return Ok(()),
+    };
+    let categories = game_categories(app, |uuid| {
+        export
+            .categories
+            .iter()
+            .any(|(c, selected)| *selected && c.uuid == uuid)
+    })?;
+    match &app.loaded_game {
+        Some(game) => export_bundle(path, game, &categories, export.history),
+        None => Ok(()),
+    }
+}
+
+/// Loads the categories of the loaded game accepted by `filter`, using the unsaved state
+/// of the loaded one.
+fn game_categories(app: &HitSplit, filter: impl Fn(&str) -> bool) -> Result<Vec<Category>, Error> {
+    let game = match &app.loaded_game {
+        Some(g) => g,
+        None => return Ok(Vec::new()),
+    };
+    let mut categories = Vec::new();
+    for category in game.categories.iter().filter(|c| filter(&c.uuid)) {
+        categories.push(match &app.loaded_category {
+            Some(c) if c.uuid == category.uuid => c.clone(),
+            _ => Category::load(category.uuid.clone())?,
+        });
+    }
+    Ok(categories)
+}
+
+/// Game chosen in the import preview, or a new one named `name`.
+fn import_target_game(app: &HitSplit, name: &str) -> Result<(Game, bool), Error> {
+    match &app.import_game {
+        Some(uuid) => Ok((Game::load(uuid.clone())?, false)),
+        None => Ok((Game::new(Uuid::new_v4().to_string(), name.to_owned()), true)),
+    }
+}
+
+/// Saves the game the categories were imported into and loads `category`.
+fn finish_import(
+    app: &mut HitSplit,
+    game: Game,
+    new_game: bool,
+    category: Category,
+) -> Result<(), Error> {
+    game.save()?;
+    if new_game {
+        app.config.game_list.push(SmallGame {
+            uuid: game.uuid.clone(),
+            name: game.name.clone(),
+        });
+        app.config.save()?;
+    }
+
+    app.num_splits_category = category.splits.len();
+    app.loaded_game = Some(game);
+    app.loaded_category = Some(category);
+    Ok(())
+}
+
+fn import_game_select(
+    ui: &mut egui::Ui,
+    game_list: &[SmallGame],
+    import_game: &mut Option<String>,
+    game_name: &mut String,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Game: ");
+        let selected = match import_game {
+            Some(uuid) => game_list
+                .iter()
+                .find(|g| &g.uuid == uuid)
+                .map(|g| g.name.clone())
+                .unwrap_or_default(),
+            None => "New game".to_string(),
+        };
+        egui::ComboBox::from_id_salt("import_game")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(import_game, None, "New game");
+                game_list.iter().for_each(|game| {
+                    ui.selectable_value(import_game, Some(game.uuid.clone()), game.name.clone());
+                });
+            });
+        if import_game.is_none() {
+            ui.add(egui::TextEdit::singleline(game_name));
+        }
+    });
+}
+
+fn import_lss(app: &mut HitSplit) -> Result<(), Error> {
+    let import = match app.lss_import.take() {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+    let (mut game, new_game) = import_target_game(app, &import.game_name)?;
+    let category = import.to_category(&mut game)?;
+    category.save()?;
+    finish_import(app, game, new_game, category)
+}
+
+pub fn lss_import_preview(app: &mut HitSplit, ctx: &Context) {
+    let import = match app.lss_import.as_mut() {
+        Some(i) => i,
+        None => return,
+    };
+    let mut confirm = false;
+    let mut cancel = false;
+
+    egui::Window::new("Import LiveSplit splits")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Import LiveSplit splits");
+            import_game_select(
+                ui,
+                &app.config.game_list,
+                &mut app.import_game,
+                &mut import.game_name,
+            );
+            ui.horizontal(|ui| {
+                ui.label("Category: ");
+                ui.add(egui::TextEdit::singleline(&mut import.category_name));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Start offset: ");
+                ui.label(duration_chrono_format(
+                    import.offset,
+                    &app.config.chrono_format,
+                ));
+            });
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("lss_import_splits")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Image");
+                            ui.strong("Name");
+                            ui.strong("PB time");
+                            ui.end_row();
+                            import.segments.iter().enumerate().for_each(|(i, segment)| {
+                                match &segment.icon {
+                                    Some(icon) => {
+                                        ui.add(
+                                            egui::Image::from_bytes(
+                                                format!(
+                                                    "bytes://lss/{}/{i}.{}",
+                                                    import.id, icon.extension
+                                                ),
+                                                icon.bytes.clone(),
+                                            )
+                                            .max_height(16.0),
+                                        );
+                                    }
+                                    None => {
+                                        ui.label("");
+                                    }
+                                }
+                                ui.label(&segment.name);
+                                ui.label(match segment.pb_time {
+                                    Some(time) => {
+                                        duration_chrono_format(time, &app.config.chrono_format)
+                                    }
+                                    None => "-".to_string(),
+                                });
+                                ui.end_row();
+                            });
+                        });
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.small_button("Import").clicked() {
+                    confirm = true;
+                }
+                if ui.small_button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if confirm {
+        if let Err(e) = import_lss(app) {
+            app.errors.report(e);
+        }
+    } else if cancel {
+        app.lss_import = None;
+    }
+}
+
+fn import_hcm(app: &mut HitSplit) -> Result<(), Error> {
+    let import = match app.hcm_import.take() {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+    let (mut game, new_game) = import_target_game(app, &import.game_name)?;
+    let mut first = None;
+    for profile in import.profiles.iter().filter(|p| p.selected) {
+        let category = profile.to_category(&mut game);
+        category.save()?;
+        first.get_or_insert(category);
+    }
+    match first {
+        Some(category) => finish_import(app, game, new_game, category),
+        None => Ok(()),
+    }
+}
+
+pub fn hcm_import_preview(app: &mut HitSplit, ctx: &Context) {
+    let import = match app.hcm_import.as_mut() {
+        Some(i) => i,
+        None => return,
+    };
+    let mut confirm = false;
+    let mut cancel = false;
+
+    egui::Window::new("Import HitCounterManager profiles")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Import HitCounterManager profiles");
+            import_game_select(
+                ui,
+                &app.config.game_list,
+                &mut app.import_game,
+                &mut import.game_name,
+            );
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("hcm_import_profiles")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Profile");
+                            ui.strong("Splits");
+                            ui.strong("Hits");
+                            ui.strong("PB");
+                            ui.strong("Attempts");
+                            ui.end_row();
+                            import.profiles.iter_mut().for_each(|profile| {
+                                ui.checkbox(&mut profile.selected, &profile.name);
+                                ui.label(profile.rows.len().to_string());
+                                let hits = profile
+                                    .rows
+                                    .iter()
+                                    .map(|row| u32::from(row.hits) + u32::from(row.way_hits));
+                                ui.label(hits.sum::<u32>().to_string());
+                                let pb = profile.rows.iter().map(|row| u32::from(row.pb));
+                                ui.label(pb.sum::<u32>().to_string());
+                                ui.label(profile.attempts.to_string());
+                                ui.end_row();
+                            });
+                        });
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.small_button("Import").clicked() {
+                    confirm = true;
+                }
+                if ui.small_button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if confirm {
+        if let Err(e) = import_hcm(app) {
+            app.errors.report(e);
+        }
+    } else if cancel {
+        app.hcm_import = None;
+    }
+}
+
+pub fn bundle_export_options(app: &mut HitSplit, ctx: &Context) {
+    let export = match app.bundle_export.as_mut() {
+        Some(e) => e,
+        None => return,
+    };
+    if app.file_action_dialog.is_some() {
+        return;
+    }
+    let mut confirm = false;
+    let mut cancel = false;
+
+    egui::Window::new("Export game bundle")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Export game bundle");
+            ui.label("Categories: ");
+            export
+                .categories
+                .iter_mut()
+                .for_each(|(category, selected)| {
+                    ui.checkbox(selected, &category.name);
+                });
+            ui.separator();
+            ui.checkbox(&mut export.history, "Include attempt history");
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.small_button("Export").clicked() {
+                    confirm = true;
+                }
+                if ui.small_button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if confirm {
+        if let Some(game) = &app.loaded_game {
+            app.file_action = Some(FileAction::ExportBundle);
+            app.file_action_dialog = Some(save_file_dialog(format!("{}.zip", game.name)));
+        }
+    } else if cancel {
+        app.bundle_export = None;
+    }
+}
+
+fn import_bundle(app: &mut HitSplit) -> Result<(), Error> {
+    let import = match app.bundle_import.take() {
+        Some(i) if i.categories.iter().any(|c| c.selected) => i,
+        _ => return Ok(()),
+    };
+    let existing = match &app.loaded_game {
+        Some(game) if game.uuid == import.game.uuid => Some(game.clone()),
+        _ if app
+            .config
+            .game_list
+            .iter()
+            .any(|g| g.uuid == import.game.uuid) =>
+        {
+            Some(Game::load(import.game.uuid.clone())?)
+        }
+        _ => None,
+    };
+    let new_game = existing.is_none() || import.as_copy;
+
+    match import.import(existing)? {
+        (game, Some(category)) => finish_import(app, game, new_game, category),
+        (_, None) => Ok(()),
+    }
+}
+
+pub fn bundle_import_preview(app: &mut HitSplit, ctx: &Context) {
+    let import = match app.bundle_import.as_mut() {
+        Some(i) => i,
+        None => return,
+    };
+    let mut confirm = false;
+    let mut cancel = false;
+
+    egui::Window::new("Import game bundle")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Import game bundle");
+            ui.horizontal(|ui| {
+                ui.label("Game: ");
+                ui.label(&import.game.name);
+            });
+            if app
+                .config
+                .game_list
+                .iter()
+                .any(|g| g.uuid == import.game.uuid)
+            {
+                ui.label("This game is already installed.");
+                ui.checkbox(&mut import.as_copy, "Import as a separate copy");
+            }
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("bundle_import_categories")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Category");
+                            ui.strong("Splits");
+                            ui.strong("Attempts");
+                            ui.end_row();
+                            import.categories.iter_mut().for_each(|bundled| {
+                                ui.checkbox(&mut bundled.selected, &bundled.category.name);
+                                ui.label(bundled.category.splits.len().to_string());
+                                let attempts = bundled
+                                    .history
+                                    .as_ref()
+                                    .map(|h| h.attempts.len())
+                                    .unwrap_or_default();
+                                ui.label(attempts.to_string());
+                                ui.end_row();
+                            });
+                        });
+                });
+            ui.separator();
+            ui.checkbox(&mut import.hits, "Import current hits and times");
+            ui.checkbox(&mut import.pb, "Import personal best");
+            ui.checkbox(&mut import.history, "Import attempt history");
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.small_button("Import").clicked() {
+                    confirm = true;
+                }
+                if ui.small_button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if confirm {
+        if let Err(e) = import_bundle(app) {
+            app.errors.report(e);
+        }
+    } else if cancel {
+        app.bundle_import = None;
+    }
+}
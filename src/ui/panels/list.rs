@@ -4,20 +4,41 @@ use egui::{Color32, Context};
 use uuid::Uuid;
 
 use crate::{
+    files::all_backups,
+    formats::{bundle::BundleExport, csv::CsvTable},
     get_file_dialog,
     run::{
+        assets,
         category::{Category, SmallCategory},
+        chrono::SignedDuration,
         game::{Game, SmallGame},
         split::Split,
     },
-    settings::columns::{Column, ColumnVec},
+    settings::{
+        columns::{Column, ColumnVec},
+        profile::{active_profile, create_profile, profiles},
+        shortcut::ShortcutTarget,
+    },
     ui::{
         functions::{image_button, numeric_edit_field_u16, numeric_edit_field_usize},
-        ChangeImage,
+        ChangeImage, FileAction,
     },
     HitSplit,
 };
 
+use super::{
+    backups::restore_backup_browser,
+    check::{data_check_window, open_data_check},
+    exchange::{
+        bundle_export_options, bundle_import_preview, file_action_dialog, hcm_import_preview,
+        lss_import_preview, open_file_action, save_file_dialog,
+    },
+    shortcuts::shortcut_overrides,
+};
+
+/// Largest start offset, in seconds, that can be set in either direction.
+const MAX_START_OFFSET: f64 = 3600.0;
+
 const FILE_EXTENSIONS: [Option<&'static str>; 3] = [Some("png"), Some("jpg"), Some("jpeg")];
 
 fn add_game(app: &mut HitSplit, ctx: &Context) {
@@ -44,7 +65,7 @@ fn add_game(app: &mut HitSplit, ctx: &Context) {
                         });
                         let game = Game::new(uuid, app.add_game_name.clone());
                         if let Err(e) = game.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                         app.add_game_name = "".to_string();
                         app.add_game_empty = false;
@@ -69,6 +90,34 @@ fn add_game(app: &mut HitSplit, ctx: &Context) {
         });
 }
 
+fn add_profile(app: &mut HitSplit, ctx: &Context) {
+    egui::Window::new("Add profile")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .open(&mut app.add_profile_open.clone())
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| ui.label("Enter the name of the profile"));
+            ui.horizontal(|ui| ui.add(egui::TextEdit::singleline(&mut app.add_profile_name)));
+            ui.horizontal(|ui| {
+                if ui.small_button("Add").clicked() {
+                    match create_profile(&app.add_profile_name) {
+                        Ok(_) => {
+                            let profile = std::mem::take(&mut app.add_profile_name);
+                            app.add_profile_open = false;
+                            app.switch_profile(&profile, ctx);
+                        }
+                        Err(e) => app.errors.report(e),
+                    }
+                }
+                if ui.small_button("Cancel").clicked() {
+                    app.add_profile_name = "".to_string();
+                    app.add_profile_open = false;
+                }
+            })
+        });
+}
+
 fn modify_game(app: &mut HitSplit, ctx: &Context) {
     egui::Window::new("Modify game name")
         .fixed_pos([50., 50.])
@@ -96,7 +145,7 @@ fn modify_game(app: &mut HitSplit, ctx: &Context) {
                             game.change_name(&app.add_game_name)
                         };
                         if let Err(e) = game.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                         app.add_game_name = "".to_string();
                         app.add_game_empty = false;
@@ -148,10 +197,10 @@ fn add_category(app: &mut HitSplit, ctx: &Context) {
                         app.add_category_name = "".to_string();
                         app.add_category_empty = false;
                         if let Err(e) = game.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                         if let Err(e) = category.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                         app.loaded_category = Some(category);
                         app.num_splits_category = 0;
@@ -196,15 +245,15 @@ fn modify_category(app: &mut HitSplit, ctx: &Context) {
                                 cat.change_name(&app.add_category_name);
                             };
                             if let Err(e) = category.save() {
-                                app.error = e;
+                                app.errors.report(e);
                             }
                             app.add_category_name = "".to_string();
                             app.add_category_empty = false;
                             if let Err(e) = game.save() {
-                                app.error = e;
+                                app.errors.report(e);
                             }
                             if let Err(e) = category.save() {
-                                app.error = e;
+                                app.errors.report(e);
                             }
                             app.modify_category_open = false;
                         };
@@ -234,9 +283,18 @@ fn column_check(ui: &mut egui::Ui, columns: &mut ColumnVec, column: &Column) {
 
 pub fn list(app: &mut HitSplit, ctx: &Context) {
     add_game(app, ctx);
+    add_profile(app, ctx);
     modify_game(app, ctx);
     add_category(app, ctx);
     modify_category(app, ctx);
+    file_action_dialog(app, ctx);
+    lss_import_preview(app, ctx);
+    hcm_import_preview(app, ctx);
+    bundle_import_preview(app, ctx);
+    bundle_export_options(app, ctx);
+    restore_backup_browser(app, ctx);
+    data_check_window(app, ctx);
+    shortcut_overrides(app, ctx);
 
     egui::CentralPanel::default().show(ctx, |ui| {
         // The central panel the region left after adding TopPanel's and SidePanel's
@@ -245,6 +303,26 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
             Some(loaded) => loaded.uuid,
         };
 
+        ui.horizontal(|ui| {
+            ui.label("Profile: ");
+            let active = active_profile();
+            let mut selected = active.clone();
+            egui::ComboBox::new("profile", "")
+                .selected_text(&active)
+                .show_ui(ui, |ui| {
+                    ui.set_min_width(60.0);
+                    profiles().into_iter().for_each(|profile| {
+                        ui.selectable_value(&mut selected, profile.clone(), profile);
+                    });
+                });
+            if selected != active {
+                app.switch_profile(&selected, ctx);
+            }
+            if ui.small_button("Add profile").clicked() {
+                app.add_profile_open = true;
+            }
+        });
+
         ui.heading("Splits");
 
         ui.horizontal(|ui| {
@@ -272,7 +350,7 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                             app.loaded_game = match Game::load(selected_game.clone()) {
                                 Ok(g) => Some(g),
                                 Err(e) => {
-                                    app.error = e;
+                                    app.errors.report(e);
                                     None
                                 }
                             };
@@ -283,6 +361,38 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
             if ui.small_button("Add game").clicked() {
                 app.add_game_open = true;
             }
+            if ui.small_button("Import LiveSplit splits").clicked() {
+                open_file_action(app, FileAction::ImportLss, "lss");
+            }
+            if ui
+                .small_button("Import HitCounterManager profiles")
+                .clicked()
+            {
+                open_file_action(app, FileAction::ImportHcm, "xml");
+            }
+            if ui.small_button("Import game bundle").clicked() {
+                open_file_action(app, FileAction::ImportBundle, "zip");
+            }
+            if ui.small_button("Restore from backup").clicked() {
+                app.backups = Some(all_backups());
+            }
+            if ui.small_button("Check data").clicked() {
+                open_data_check(app);
+            }
+            if ui.small_button("Clean up unused images").clicked() {
+                // The loaded game and category may have images that are not saved yet.
+                let mut in_use = Vec::new();
+                if let Some(g) = &app.loaded_game {
+                    in_use.push(&g.icon_path);
+                }
+                if let Some(c) = &app.loaded_category {
+                    in_use.push(&c.icon_path);
+                    in_use.extend(c.splits.iter().map(|s| &s.icon_path));
+                }
+                if let Err(e) = assets::cleanup(&in_use) {
+                    app.errors.report(e);
+                }
+            }
             if let Some(g) = &mut app.loaded_game {
                 if ui.small_button("Modify game name").clicked() {
                     app.modify_game_open = true;
@@ -295,7 +405,7 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                 .any(|fe| fe.map(OsStr::new) == path.extension())
                         }
                     });
-                    let mut dialog = get_file_dialog(g.icon_path.clone()).show_files_filter(filter);
+                    let mut dialog = get_file_dialog(None).show_files_filter(filter);
                     dialog.open();
                     app.change_image = Some(ChangeImage::Game);
                     app.open_file_dialog = Some(dialog);
@@ -303,6 +413,20 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                 if ui.small_button("Clear game image").clicked() {
                     g.icon_path = None;
                 }
+                if ui.small_button("Game shortcuts").clicked() {
+                    app.shortcut_overrides = Some(ShortcutTarget::Game);
+                }
+                if ui.small_button("Export game bundle").clicked() {
+                    app.bundle_export = Some(BundleExport::new(g));
+                }
+                if ui
+                    .small_button("Export HitCounterManager profiles")
+                    .clicked()
+                {
+                    app.file_action = Some(FileAction::ExportHcm);
+                    app.file_action_dialog =
+                        Some(save_file_dialog("HitCounterManagerSave.xml".to_string()));
+                }
             }
         });
 
@@ -335,17 +459,12 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                             {
                                 app.loaded_category =
                                     match Category::load(selected_category.to_string()) {
-                                        Ok(mut c) => {
+                                        Ok(c) => {
                                             app.num_splits_category = c.splits.len();
-                                            c.splits.iter_mut().for_each(|s| {
-                                                if s.uuid.is_none() {
-                                                    s.uuid = Some(Uuid::new_v4().to_string());
-                                                }
-                                            });
                                             Some(c)
                                         }
                                         Err(e) => {
-                                            app.error = e;
+                                            app.errors.report(e);
                                             None
                                         }
                                     };
@@ -367,8 +486,7 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                     .any(|fe| fe.map(OsStr::new) == path.extension())
                             }
                         });
-                        let mut dialog =
-                            get_file_dialog(c.icon_path.clone()).show_files_filter(filter);
+                        let mut dialog = get_file_dialog(None).show_files_filter(filter);
                         dialog.open();
                         app.change_image = Some(ChangeImage::Category);
                         app.open_file_dialog = Some(dialog);
@@ -376,6 +494,31 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                     if ui.small_button("Clear category image").clicked() {
                         c.icon_path = None;
                     }
+                    if ui.small_button("Category shortcuts").clicked() {
+                        app.shortcut_overrides = Some(ShortcutTarget::Category);
+                    }
+                    ui.menu_button("Export", |ui| {
+                        let exports = [
+                            ("LiveSplit splits", FileAction::ExportLss, "lss"),
+                            ("splits.io run", FileAction::ExportSplitsIo, "json"),
+                            ("Splits CSV", FileAction::ExportCsv(CsvTable::Splits), "csv"),
+                            (
+                                "Attempt history CSV",
+                                FileAction::ExportCsv(CsvTable::History),
+                                "csv",
+                            ),
+                        ];
+                        for (label, action, extension) in exports {
+                            if ui.button(label).clicked() {
+                                app.file_action = Some(action);
+                                app.file_action_dialog = Some(save_file_dialog(format!(
+                                    "{} - {}.{extension}",
+                                    g.name, c.name
+                                )));
+                                ui.close_menu();
+                            }
+                        }
+                    });
                 }
             });
 
@@ -384,6 +527,22 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                     ui.label("Number of splits: ");
                     numeric_edit_field_usize(ui, &mut app.num_splits_category);
                 });
+                ui.horizontal(|ui| {
+                    ui.label("Start offset: ");
+                    let mut offset = c.start_offset.as_secs_f64();
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut offset)
+                                .speed(0.01)
+                                .fixed_decimals(2)
+                                .range(-MAX_START_OFFSET..=MAX_START_OFFSET),
+                        )
+                        .changed()
+                    {
+                        c.start_offset = SignedDuration::from_secs_f64(offset);
+                    }
+                    ui.label("seconds");
+                });
 
                 if ui.small_button("Create table").clicked() {
                     let cmp_splits: i16 = c.splits.len() as i16 - app.num_splits_category as i16;
@@ -398,7 +557,7 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                         }
                     }
                     if let Err(e) = c.save() {
-                        app.error = e;
+                        app.errors.report(e);
                     }
                 }
 
@@ -415,6 +574,8 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                         .column(egui_extras::Column::auto())
                         .column(egui_extras::Column::auto())
                         .column(egui_extras::Column::auto())
+                        .column(egui_extras::Column::auto())
+                        .column(egui_extras::Column::auto())
                         .column(egui_extras::Column::initial(24.0))
                         .column(egui_extras::Column::initial(24.0))
                         .min_scrolled_height(0.0);
@@ -433,6 +594,10 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                 ui.strong("Hits");
                                 column_check(ui, &mut app.config.columns, &Column::Hits);
                             });
+                            header.col(|ui| {
+                                ui.strong("Way hits");
+                                column_check(ui, &mut app.config.columns, &Column::WayHits);
+                            });
                             header.col(|ui| {
                                 ui.strong("Diff");
                                 column_check(ui, &mut app.config.columns, &Column::Difference);
@@ -449,6 +614,10 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                 ui.strong("Chrono Ac.");
                                 column_check(ui, &mut app.config.columns, &Column::ChronoAcum);
                             });
+                            header.col(|ui| {
+                                ui.strong("Paused");
+                                column_check(ui, &mut app.config.columns, &Column::PausedTime);
+                            });
                             header.col(|ui| {
                                 ui.strong("");
                             });
@@ -461,36 +630,27 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                 body.row(24., |mut row| {
                                     row.col(|ui| {
                                         if let Some(p) = &split.icon_path {
-                                            if let Some(path) = p.to_str() {
-                                                if ui
-                                                    .add(image_button(
-                                                        format!("file://{path}"),
-                                                        16.0,
-                                                        16.0,
-                                                        0.0,
-                                                    ))
-                                                    .clicked()
-                                                {
-                                                    let filter = Box::new({
-                                                        move |path: &Path| -> bool {
-                                                            FILE_EXTENSIONS.iter().any(|fe| {
-                                                                fe.map(OsStr::new)
-                                                                    == path.extension()
-                                                            })
-                                                        }
-                                                    });
-                                                    let mut dialog =
-                                                        get_file_dialog(Some(p.to_path_buf()))
-                                                            .show_files_filter(filter);
-                                                    dialog.open();
-                                                    app.open_file_dialog = Some(dialog);
-                                                    if let Some(uuid) = split.uuid.clone() {
-                                                        app.change_image.clone_from(&Some(
-                                                            ChangeImage::Split(uuid),
-                                                        ));
+                                            if ui
+                                                .add(image_button(assets::uri(p), 16.0, 16.0, 0.0))
+                                                .clicked()
+                                            {
+                                                let filter = Box::new({
+                                                    move |path: &Path| -> bool {
+                                                        FILE_EXTENSIONS.iter().any(|fe| {
+                                                            fe.map(OsStr::new) == path.extension()
+                                                        })
                                                     }
+                                                });
+                                                let mut dialog =
+                                                    get_file_dialog(None).show_files_filter(filter);
+                                                dialog.open();
+                                                app.open_file_dialog = Some(dialog);
+                                                if let Some(uuid) = split.uuid.clone() {
+                                                    app.change_image.clone_from(&Some(
+                                                        ChangeImage::Split(uuid),
+                                                    ));
                                                 }
-                                            };
+                                            }
                                         } else if ui.button("Add image").clicked() {
                                             let filter = Box::new({
                                                 move |path: &Path| -> bool {
@@ -523,9 +683,12 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                     row.col(|ui| {
                                         numeric_edit_field_u16(ui, &mut split.hits);
                                     });
+                                    row.col(|ui| {
+                                        numeric_edit_field_u16(ui, &mut split.way_hits);
+                                    });
                                     row.col(|ui| {
                                         ui.label(
-                                            (i32::from(split.hits) - i32::from(split.pb))
+                                            (i32::from(split.total_hits()) - i32::from(split.pb))
                                                 .to_string(),
                                         );
                                     });
@@ -534,6 +697,7 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                     });
                                     row.col(|_ui| {});
                                     row.col(|_ui| {});
+                                    row.col(|_ui| {});
                                     row.col(|ui| {
                                         if ui.button("➕").clicked() {
                                             app.add_split_under = Some(i);
@@ -556,10 +720,13 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                     ui.label(hits.sum::<u16>().to_string());
                                 });
                                 row.col(|ui| {
-                                    let diffs = c
-                                        .splits
-                                        .iter()
-                                        .map(|split| i32::from(split.hits) - i32::from(split.pb));
+                                    let hits = c.splits.iter().map(|split| split.way_hits);
+                                    ui.label(hits.sum::<u16>().to_string());
+                                });
+                                row.col(|ui| {
+                                    let diffs = c.splits.iter().map(|split| {
+                                        i32::from(split.total_hits()) - i32::from(split.pb)
+                                    });
                                     ui.label(diffs.sum::<i32>().to_string());
                                 });
                                 row.col(|ui| {
@@ -570,6 +737,7 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                 row.col(|_ui| {});
                                 row.col(|_ui| {});
                                 row.col(|_ui| {});
+                                row.col(|_ui| {});
                             });
                         });
 
@@ -577,16 +745,17 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                         c.splits.remove(split);
                         app.delete_split = None;
                         if let Err(e) = c.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                         app.num_splits_category = c.splits.len();
                     }
 
                     if let Some(split) = app.add_split_under {
-                        c.splits.insert(split + 1, Split::default());
+                        c.splits
+                            .insert(split + 1, Split::new(Some(Uuid::new_v4().to_string())));
                         app.add_split_under = None;
                         if let Err(e) = c.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                         app.num_splits_category = c.splits.len();
                     }
@@ -594,12 +763,17 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                     if let Some(dialog) = &mut app.open_file_dialog {
                         if dialog.show(ctx).selected() {
                             if let Some(file) = dialog.path() {
-                                if let Some(change) = &app.change_image {
+                                let icon = match assets::store_file(file) {
+                                    Ok(reference) => Some(reference),
+                                    Err(e) => {
+                                        app.errors.report(e);
+                                        None
+                                    }
+                                };
+                                if let (Some(change), Some(icon)) = (&app.change_image, icon) {
                                     match change {
-                                        ChangeImage::Game => g.icon_path = Some(file.to_path_buf()),
-                                        ChangeImage::Category => {
-                                            c.icon_path = Some(file.to_path_buf())
-                                        }
+                                        ChangeImage::Game => g.icon_path = Some(icon),
+                                        ChangeImage::Category => c.icon_path = Some(icon),
                                         ChangeImage::Split(uuid) => {
                                             if let Some(split) = c.splits.iter_mut().find(|s| {
                                                 if let Some(s_uuid) = s.uuid.clone() {
@@ -608,7 +782,7 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                                                     false
                                                 }
                                             }) {
-                                                split.icon_path = Some(file.to_path_buf());
+                                                split.icon_path = Some(icon);
                                             }
                                         }
                                     };
@@ -622,10 +796,10 @@ pub fn list(app: &mut HitSplit, ctx: &Context) {
                 ui.horizontal(|ui| {
                     if ui.button("Save splits").clicked() {
                         if let Err(e) = g.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                         if let Err(e) = c.save() {
-                            app.error = e;
+                            app.errors.report(e);
                         }
                     }
                 });
@@ -0,0 +1,61 @@
+use egui::{Color32, Context};
+
+use crate::{
+    logging::{log_path, tail},
+    HitSplit,
+};
+
+/// Lines of the log shown in the viewer.
+const LOG_VIEW_LINES: usize = 500;
+
+pub fn open_log_viewer(app: &mut HitSplit) {
+    app.log_lines = Some(tail(LOG_VIEW_LINES));
+}
+
+/// Shows the end of the log, errors and warnings in colour.
+pub fn log_viewer(app: &mut HitSplit, ctx: &Context) {
+    let Some(lines) = &app.log_lines else {
+        return;
+    };
+    let mut refresh = false;
+    let mut close = false;
+
+    egui::Window::new("Log")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading("Log");
+            ui.label(log_path());
+            if lines.is_empty() {
+                ui.label("The log is empty.");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    lines.iter().for_each(|line| {
+                        match line.split_whitespace().nth(1) {
+                            Some("ERROR") => ui.colored_label(Color32::from_rgb(250, 8, 8), line),
+                            Some("WARN") => ui.colored_label(Color32::from_rgb(240, 107, 12), line),
+                            _ => ui.label(line),
+                        };
+                    });
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.small_button("Refresh").clicked() {
+                    refresh = true;
+                }
+                if ui.small_button("Close").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if refresh {
+        open_log_viewer(app);
+    } else if close {
+        app.log_lines = None;
+    }
+}
@@ -1,11 +1,20 @@
-use egui::{Color32, Context};
+use egui::Context;
 
 use crate::HitSplit;
 
-use super::{functions::image_button, VERSION};
+use super::{
+    functions::image_button,
+    notifications::{error_bar, error_list},
+    VERSION,
+};
 
+pub mod backups;
+pub mod check;
+pub mod exchange;
 pub mod list;
+pub mod log;
 pub mod settings;
+pub mod shortcuts;
 
 #[derive(Clone)]
 pub enum Pages {
@@ -53,12 +62,9 @@ pub fn left_panel(app: &mut HitSplit, ctx: &Context) {
 }
 
 pub fn bottom_panel(app: &mut HitSplit, ctx: &Context) {
+    error_list(app, ctx);
     egui::TopBottomPanel::bottom("error_panel")
         .exact_height(20.0)
         .resizable(false)
-        .show(ctx, |ui| {
-            if let crate::Error::Error(e) = &app.error {
-                ui.colored_label(Color32::from_rgb(250, 8, 8), e);
-            }
-        });
+        .show(ctx, |ui| error_bar(app, ui));
 }
@@ -1,19 +1,49 @@
 use egui::{color_picker::color_edit_button_srgb, Slider};
 
 use crate::{
-    run::chrono::ChronometerFormat,
-    settings::{config::Config, shortcut::ShortcutAction},
-    ui::functions::{numeric_edit_field_u64, shortcut_button},
+    get_config_path,
+    logging::Level,
+    settings::{
+        columns::Column,
+        config::Config,
+        shortcut::{ShortcutAction, ShortcutTarget},
+    },
+    ui::functions::{chrono_format_edit, numeric_edit_field_u64, shortcut_button},
     Error, HitSplit,
 };
 
+use super::log::{log_viewer, open_log_viewer};
+
 pub fn configuration(app: &mut HitSplit, ctx: &egui::Context) {
+    log_viewer(app, ctx);
+
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.heading("Configuration");
+        ui.horizontal(|ui| {
+            ui.label("Data folder: ");
+            ui.label(get_config_path());
+        });
+        ui.horizontal(|ui| {
+            ui.label("Log level: ");
+            egui::ComboBox::new("log_level", "")
+                .selected_text(app.config.log_level.to_string())
+                .show_ui(ui, |ui| {
+                    Level::ALL.into_iter().for_each(|level| {
+                        ui.selectable_value(&mut app.config.log_level, level, level.to_string());
+                    });
+                });
+            if ui.small_button("View log").clicked() {
+                open_log_viewer(app);
+            }
+        });
         ui.horizontal(|ui| {
             ui.label("Visual mode: ");
             egui::widgets::global_theme_preference_buttons(ui);
         });
+        ui.horizontal(|ui| {
+            ui.label("Runner name: ");
+            ui.text_edit_singleline(&mut app.config.runner_name);
+        });
         ui.horizontal(|ui| {
             ui.label("Autosave: ");
             ui.checkbox(&mut app.config.autosave, "");
@@ -55,44 +85,33 @@ pub fn configuration(app: &mut HitSplit, ctx: &egui::Context) {
             });
         }
         ui.horizontal(|ui| {
-            ui.label("Chronometer format");
-            let chrono_format = &app.config.chrono_format.clone();
-            let fun = |ui: &mut egui::Ui| {
-                ui.selectable_value(
-                    &mut app.config.chrono_format,
-                    ChronometerFormat::HHMM,
-                    "H:MM",
-                );
-                ui.selectable_value(
-                    &mut app.config.chrono_format,
-                    ChronometerFormat::HHMMSS,
-                    "H:MM:SS",
-                );
-                ui.selectable_value(
-                    &mut app.config.chrono_format,
-                    ChronometerFormat::HHMMSSX,
-                    "H:MM:SS.cs",
-                );
-                ui.selectable_value(
-                    &mut app.config.chrono_format,
-                    ChronometerFormat::MMSS,
-                    "MM:SS",
-                );
-                ui.selectable_value(
-                    &mut app.config.chrono_format,
-                    ChronometerFormat::MMSSX,
-                    "MM:SS.cs",
-                );
-            };
-            if egui::ComboBox::from_id_salt("chrono_format")
-                .selected_text(chrono_format.text())
-                .show_ui(ui, fun)
-                .response
-                .changed()
-            {
-                app.chrono.set_format(chrono_format);
+            ui.label("Chronometer format: ");
+            if chrono_format_edit(ui, "chrono_format", &mut app.config.chrono_format) {
+                app.chrono.set_format(&app.config.chrono_format);
             }
         });
+        for (column, name) in [
+            (Column::Chrono, "Chrono"),
+            (Column::ChronoAcum, "Chrono Ac."),
+            (Column::PausedTime, "Paused"),
+        ] {
+            ui.horizontal(|ui| {
+                ui.label(format!("\"{name}\" column format: "));
+                let mut custom = app.config.column_formats.contains_key(&column);
+                if ui.checkbox(&mut custom, "Custom").changed() {
+                    if custom {
+                        app.config
+                            .column_formats
+                            .insert(column, app.config.chrono_format.clone());
+                    } else {
+                        app.config.column_formats.remove(&column);
+                    }
+                }
+                if let Some(format) = app.config.column_formats.get_mut(&column) {
+                    chrono_format_edit(ui, &format!("column_format_{name}"), format);
+                }
+            });
+        }
         if cfg!(target_os = "linux") {
             ui.horizontal(|ui| {
                 ui.label("Counter background transparency: ");
@@ -141,63 +160,47 @@ pub fn configuration(app: &mut HitSplit, ctx: &egui::Context) {
             ui.label("Counter always on top: ");
             ui.checkbox(&mut app.config.always_on_top, "");
         });
-        ui.separator();
-        ui.heading("Shortcuts");
-        ui.horizontal(|ui| {
-            ui.label("Previous split: ");
-            shortcut_button(app, ui, &ShortcutAction::PrevSplit);
-        });
         ui.horizontal(|ui| {
-            ui.label("Next split: ");
-            shortcut_button(app, ui, &ShortcutAction::NextSplit);
-        });
-        ui.horizontal(|ui| {
-            ui.label("Add hit: ");
-            shortcut_button(app, ui, &ShortcutAction::AddHit);
-        });
-        ui.horizontal(|ui| {
-            ui.label("Substract hit: ");
-            shortcut_button(app, ui, &ShortcutAction::SubHit);
-        });
-        ui.horizontal(|ui| {
-            ui.label("Reset: ");
-            shortcut_button(app, ui, &ShortcutAction::Reset);
-        });
-        ui.horizontal(|ui| {
-            ui.label("Set current run as PB: ");
-            shortcut_button(app, ui, &ShortcutAction::SetPb);
-        });
-        ui.horizontal(|ui| {
-            ui.label("Start chrono: ");
-            shortcut_button(app, ui, &ShortcutAction::StartChrono);
-        });
-        ui.horizontal(|ui| {
-            ui.label("Pause chrono: ");
-            shortcut_button(app, ui, &ShortcutAction::PauseChrono);
+            ui.label("LiveSplit Server compatible TCP interface: ");
+            let mut changed = ui.checkbox(&mut app.config.tcp_server, "").changed();
+            if app.config.tcp_server {
+                ui.label("Port: ");
+                let port = ui.add(egui::DragValue::new(&mut app.config.tcp_server_port));
+                // Restarting on every step would bind each port passed on the way.
+                changed |= port.drag_stopped() || port.lost_focus();
+            }
+            if changed {
+                app.manage_server(ctx);
+            }
         });
+        ui.separator();
+        ui.heading("Shortcuts");
+        for action in ShortcutAction::ALL {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}: ", action.name()));
+                shortcut_button(app, ui, ShortcutTarget::Global, &action);
+            });
+        }
 
         if ui.button("Save config").clicked() {
             match app.shortcut.as_ref() {
                 Some(sc) => {
                     if let Err(e) = sc.save() {
-                        app.error = e;
+                        app.errors.report(e);
                     }
                 }
-                None => {
-                    app.error = Error::new(
-                        "Could not save config due to shortcuts. Please file an issue.".to_string(),
-                        "None".to_string(),
-                    )
-                }
+                None => app.errors.report(Error::missing(
+                    "Could not save config due to shortcuts. Please file an issue.".to_string(),
+                )),
             };
             if let Some(game) = &app.loaded_game {
                 if let Err(e) = game.save() {
-                    app.error = e;
+                    app.errors.report(e);
                 };
             }
             if let Some(category) = &app.loaded_category {
                 if let Err(e) = category.save() {
-                    app.error = e;
+                    app.errors.report(e);
                 };
             }
         }
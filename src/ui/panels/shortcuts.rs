@@ -0,0 +1,79 @@
+use egui::Context;
+
+use crate::{
+    settings::shortcut::{ShortcutAction, ShortcutTarget},
+    ui::functions::shortcut_button,
+    HitSplit,
+};
+
+/// Lets the loaded game or category override the bindings of each action. Actions that are
+/// not overridden show the bindings they use instead.
+pub fn shortcut_overrides(app: &mut HitSplit, ctx: &Context) {
+    let Some(target) = app.shortcut_overrides else {
+        return;
+    };
+    let name = match target {
+        ShortcutTarget::Global => None,
+        ShortcutTarget::Game => app.loaded_game.as_ref().map(|g| g.name.clone()),
+        ShortcutTarget::Category => app.loaded_category.as_ref().map(|c| c.name.clone()),
+    };
+    let Some(name) = name else {
+        app.shortcut_overrides = None;
+        return;
+    };
+    let inherited = app.inherited_shortcut(target);
+    let mut close = false;
+
+    egui::Window::new("Shortcut overrides")
+        .fixed_pos([50., 50.])
+        .resizable(false)
+        .title_bar(false)
+        .show(ctx, |ui| {
+            ui.heading(format!("Shortcuts of {name}"));
+            ui.label("Overridden actions use these bindings while it is loaded.");
+            for action in ShortcutAction::ALL {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}: ", action.name()));
+                    let mut overridden =
+                        app.shortcut_of(target).is_some_and(|s| s.overrides(action));
+                    if ui.checkbox(&mut overridden, "Override").changed() {
+                        if let Some(overrides) = app.shortcut_mut(target) {
+                            if overridden {
+                                let bindings = inherited
+                                    .as_ref()
+                                    .map(|s| s.bindings(action).to_vec())
+                                    .unwrap_or_default();
+                                overrides.bindings.insert(action, bindings);
+                            } else {
+                                overrides.inherit(action);
+                            }
+                        }
+                    }
+                    if overridden {
+                        shortcut_button(app, ui, target, &action);
+                    } else {
+                        let bindings = inherited
+                            .as_ref()
+                            .map(|s| s.bindings(action))
+                            .unwrap_or_default();
+                        if bindings.is_empty() {
+                            ui.label("None");
+                        } else {
+                            let text: Vec<String> =
+                                bindings.iter().map(ToString::to_string).collect();
+                            ui.label(text.join(", "));
+                        }
+                    }
+                });
+            }
+            ui.separator();
+            if ui.small_button("Close").clicked() {
+                close = true;
+            }
+        });
+
+    if close {
+        app.shortcut_overrides = None;
+        app.capturing = None;
+    }
+}